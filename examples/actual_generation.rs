@@ -0,0 +1,22 @@
+use rte_france::api::actual_generation::ActualGeneration;
+use rte_france::api::DateRange;
+use rte_france::RteApi;
+
+fn main() {
+    let mut rte_api = RteApi::from_env_values();
+    rte_api.authenticate().expect("Failed to authenticate");
+
+    let ag = ActualGeneration::new(&rte_api);
+
+    let now = chrono::Utc::now();
+    let range = DateRange {
+        start: now - chrono::Duration::days(2),
+        end: now - chrono::Duration::days(1),
+    };
+
+    let per_production_type = ag.per_production_type(Some(range.clone()));
+    println!("{}", per_production_type.unwrap().as_polars_df().unwrap());
+
+    let mix = ag.generation_mix(Some(range));
+    println!("{}", mix.unwrap().as_polars_df().unwrap());
+}
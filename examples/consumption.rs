@@ -1,4 +1,5 @@
 use rte_france::api::consumption::ConsumptionForecast;
+use rte_france::api::consumption::ShortTermForecastType;
 use rte_france::api::DateRange;
 use rte_france::RteApi;
 
@@ -15,6 +16,18 @@ fn main() {
         end: in_1h + chrono::Duration::hours(35),
     };
     println!("range: {:?}", range);
+
+    let all_horizons = consumption_forecast.short_terms_df(
+        &[
+            ShortTermForecastType::Realised,
+            ShortTermForecastType::Intraday,
+            ShortTermForecastType::Tomorrow,
+            ShortTermForecastType::DayAfterTomorrow,
+        ],
+        None,
+    );
+    println!("{}", all_horizons.unwrap());
+
     //    let data =
     //        consumption_forecast.short_term(ShortTermForecastType::DayAfterTomorrow, Some(range));
     //    println!("data: {:?}", data);
@@ -22,5 +35,7 @@ fn main() {
 
     let weekly = consumption_forecast.weekly_forecast(None);
     println!("data: {:?}", weekly);
-    println!("{}", weekly.unwrap().as_polars_df().unwrap());
+    let weekly = weekly.unwrap();
+    println!("{}", weekly.as_polars_df().unwrap());
+    println!("{}", weekly.peaks_df().unwrap());
 }
@@ -0,0 +1,13 @@
+use rte_france::api::ecowatt::Ecowatt;
+use rte_france::RteApi;
+
+fn main() {
+    let mut rte_api = RteApi::from_env_values();
+    rte_api.authenticate().expect("Failed to authenticate");
+
+    let signals = Ecowatt::new(&rte_api).signals().unwrap();
+    for day in &signals.signals {
+        println!("{}: {} ({})", day.day, day.level, day.message);
+    }
+    println!("{}", signals.as_polars_df().unwrap());
+}
@@ -1,4 +1,3 @@
-use rte_france::api::generation::ForecastType;
 use rte_france::api::generation::GenerationForecast;
 use rte_france::api::generation::ProductionType;
 use rte_france::api::DateRange;
@@ -22,7 +21,10 @@ fn main() {
         None,        //Some(ForecastType::AfterAfterTomorrow),
         Some(range), //None,
     );
-    for forecast in forecast.unwrap().forecasts {
+    let forecast = forecast.unwrap();
+    println!("{}", forecast.as_polars_df().unwrap());
+    println!("{}", forecast.pivot_by_production_type().unwrap());
+    for forecast in forecast.forecasts {
         println!(
             "forecast: {:?} / {:?} / {:?}",
             forecast.ty, forecast.sub_type, forecast.production_type
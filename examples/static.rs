@@ -36,5 +36,7 @@ fn main() {
     let data = consumption_forecast.short_term(ShortTermForecastType::DayAfterTomorrow, None);
     println!("data: {:?}", data);
 
-    println!("{}", data.unwrap().as_polars_df().unwrap());
+    let data = data.unwrap();
+    println!("{}", data.as_polars_df().unwrap());
+    println!("{}", data.pivot_by_type().unwrap());
 }
@@ -0,0 +1,15 @@
+use rte_france::api::tempo::{Tempo, TempoColor};
+use rte_france::RteApi;
+
+fn main() {
+    let mut rte_api = RteApi::from_env_values();
+    rte_api.authenticate().expect("Failed to authenticate");
+
+    let tempo = Tempo::new(&rte_api);
+
+    println!("tomorrow: {:?}", tempo.next_day_color());
+    println!(
+        "remaining red days: {:?}",
+        tempo.remaining_days(TempoColor::Red)
+    );
+}
@@ -0,0 +1,23 @@
+use rte_france::api::consumption::ConsumptionForecast;
+use rte_france::api::generation::GenerationForecast;
+use rte_france::vintage_store::VintageStore;
+use rte_france::RteApi;
+
+fn main() {
+    let mut rte_api = RteApi::from_env_values();
+    rte_api.authenticate().expect("Failed to authenticate");
+
+    let gf = GenerationForecast::new(&rte_api);
+    let cf = ConsumptionForecast::new(&rte_api);
+
+    let store = VintageStore::new("vintages");
+
+    loop {
+        let generation = store.poll_generation(&gf, None, None, None);
+        println!("new generation vintages: {:?}", generation);
+        let consumption = store.poll_consumption(&cf, &[], None);
+        println!("new consumption vintages: {:?}", consumption);
+
+        std::thread::sleep(std::time::Duration::from_secs(15 * 60));
+    }
+}
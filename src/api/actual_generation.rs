@@ -0,0 +1,443 @@
+use core::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct ActualGeneration<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for ActualGeneration<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Production types of the actual generation API
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum ActualProductionType {
+    Biomass,
+    /// Biomasse, biogaz et déchets (generation mix only)
+    Bioenergy,
+    FossilGas,
+    FossilHardCoal,
+    FossilOil,
+    /// Hydraulique, toutes filières confondues (generation mix only)
+    Hydro,
+    HydroPumpedStorage,
+    HydroRunOfRiverAndPoundage,
+    HydroWaterReservoir,
+    Nuclear,
+    Solar,
+    Waste,
+    /// Eolien, terrestre et en mer (generation mix only)
+    Wind,
+    WindOffshore,
+    WindOnshore,
+    /// Consommation (generation mix only)
+    Consumption,
+    /// Solde des échanges (generation mix only)
+    Exchange,
+    /// Pompage des STEP (generation mix only)
+    Pumping,
+    /// A production type not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for ActualProductionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pt = match self {
+            ActualProductionType::Biomass => "BIOMASS",
+            ActualProductionType::Bioenergy => "BIOENERGY",
+            ActualProductionType::FossilGas => "FOSSIL_GAS",
+            ActualProductionType::FossilHardCoal => "FOSSIL_HARD_COAL",
+            ActualProductionType::FossilOil => "FOSSIL_OIL",
+            ActualProductionType::Hydro => "HYDRO",
+            ActualProductionType::HydroPumpedStorage => "HYDRO_PUMPED_STORAGE",
+            ActualProductionType::HydroRunOfRiverAndPoundage => "HYDRO_RUN_OF_RIVER_AND_POUNDAGE",
+            ActualProductionType::HydroWaterReservoir => "HYDRO_WATER_RESERVOIR",
+            ActualProductionType::Nuclear => "NUCLEAR",
+            ActualProductionType::Solar => "SOLAR",
+            ActualProductionType::Waste => "WASTE",
+            ActualProductionType::Wind => "WIND",
+            ActualProductionType::WindOffshore => "WIND_OFFSHORE",
+            ActualProductionType::WindOnshore => "WIND_ONSHORE",
+            ActualProductionType::Consumption => "CONSUMPTION",
+            ActualProductionType::Exchange => "EXCHANGE",
+            ActualProductionType::Pumping => "PUMPING",
+            ActualProductionType::Unknown(pt) => pt,
+        };
+        write!(f, "{}", pt)
+    }
+}
+
+impl From<String> for ActualProductionType {
+    fn from(pt: String) -> Self {
+        match pt.as_str() {
+            "BIOMASS" => ActualProductionType::Biomass,
+            "BIOENERGY" => ActualProductionType::Bioenergy,
+            "FOSSIL_GAS" => ActualProductionType::FossilGas,
+            "FOSSIL_HARD_COAL" => ActualProductionType::FossilHardCoal,
+            "FOSSIL_OIL" => ActualProductionType::FossilOil,
+            "HYDRO" => ActualProductionType::Hydro,
+            "HYDRO_PUMPED_STORAGE" => ActualProductionType::HydroPumpedStorage,
+            "HYDRO_RUN_OF_RIVER_AND_POUNDAGE" => ActualProductionType::HydroRunOfRiverAndPoundage,
+            "HYDRO_WATER_RESERVOIR" => ActualProductionType::HydroWaterReservoir,
+            "NUCLEAR" => ActualProductionType::Nuclear,
+            "SOLAR" => ActualProductionType::Solar,
+            "WASTE" => ActualProductionType::Waste,
+            "WIND" => ActualProductionType::Wind,
+            "WIND_OFFSHORE" => ActualProductionType::WindOffshore,
+            "WIND_ONSHORE" => ActualProductionType::WindOnshore,
+            "CONSUMPTION" => ActualProductionType::Consumption,
+            "EXCHANGE" => ActualProductionType::Exchange,
+            "PUMPING" => ActualProductionType::Pumping,
+            _ => ActualProductionType::Unknown(pt),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ActualGenerationValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub updated_date: Option<DateTime<Utc>>,
+    pub value: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PerProductionTypeResponse {
+    pub actual_generations_per_production_type: Vec<PerProductionType>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PerProductionType {
+    pub production_type: ActualProductionType,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ActualGenerationValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PerUnitResponse {
+    pub actual_generations_per_unit: Vec<PerUnit>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PerUnit {
+    pub unit: Unit,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ActualGenerationValue>,
+}
+
+/// A generation unit
+#[derive(Deserialize, Debug)]
+pub struct Unit {
+    /// EIC code of the unit
+    pub eic_code: String,
+    pub name: String,
+    pub production_type: ActualProductionType,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PerSectorResponse {
+    pub actual_generations_per_sector: Vec<PerSector>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PerSector {
+    pub sector: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ActualGenerationValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WaterReservesResponse {
+    pub water_reserves: Vec<WaterReserves>,
+}
+
+/// Weekly filling of the hydraulic reservoirs, in MWh
+#[derive(Deserialize, Debug)]
+pub struct WaterReserves {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ActualGenerationValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GenerationMixResponse {
+    pub generation_mix_15min_time_scale: Vec<GenerationMix>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GenerationMix {
+    pub production_type: ActualProductionType,
+    pub production_subtype: Option<String>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ActualGenerationValue>,
+}
+
+impl<'a> ActualGeneration<'a> {
+    const PER_PRODUCTION_TYPE_URL: &'static str =
+        "/open_api/actual_generation/v1/actual_generations_per_production_type";
+    const PER_UNIT_URL: &'static str = "/open_api/actual_generation/v1/actual_generations_per_unit";
+    const PER_SECTOR_URL: &'static str =
+        "/open_api/actual_generation/v1/actual_generations_per_sector";
+    const WATER_RESERVES_URL: &'static str = "/open_api/actual_generation/v1/water_reserves";
+    const GENERATION_MIX_URL: &'static str =
+        "/open_api/actual_generation/v1/generation_mix_15min_time_scale";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the hourly actual generation per production type
+    pub fn per_production_type(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<PerProductionTypeResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: PerProductionTypeResponse =
+            get_response(self.client, ActualGeneration::PER_PRODUCTION_TYPE_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the hourly actual generation of each unit above 100MW
+    pub fn per_unit(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<PerUnitResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: PerUnitResponse = get_response(self.client, ActualGeneration::PER_UNIT_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the hourly actual generation per sector
+    pub fn per_sector(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<PerSectorResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        get_response(self.client, ActualGeneration::PER_SECTOR_URL, &qs)
+    }
+
+    /// Returns the filling of the hydraulic reservoirs
+    pub fn water_reserves(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<WaterReservesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        get_response(self.client, ActualGeneration::WATER_RESERVES_URL, &qs)
+    }
+
+    /// Returns the generation mix on a 15 minutes time scale
+    pub fn generation_mix(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<GenerationMixResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: GenerationMixResponse =
+            get_response(self.client, ActualGeneration::GENERATION_MIX_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for PerProductionTypeResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for generation in &self.actual_generations_per_production_type {
+            if let ActualProductionType::Unknown(pt) = &generation.production_type {
+                unknown_values.push(pt.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl UnknownValues for PerUnitResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for generation in &self.actual_generations_per_unit {
+            if let ActualProductionType::Unknown(pt) = &generation.unit.production_type {
+                unknown_values.push(pt.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl UnknownValues for GenerationMixResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for generation in &self.generation_mix_15min_time_scale {
+            if let ActualProductionType::Unknown(pt) = &generation.production_type {
+                unknown_values.push(pt.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+/// Columns shared by all the actual generation frames
+#[derive(Default)]
+struct ValueColumns {
+    start_dates: Vec<NaiveDateTime>,
+    end_dates: Vec<NaiveDateTime>,
+    updated_dates: Vec<Option<NaiveDateTime>>,
+    values: Vec<f64>,
+}
+
+impl ValueColumns {
+    fn push(&mut self, v: &ActualGenerationValue) {
+        self.start_dates.push(v.start_date.naive_utc());
+        self.end_dates.push(v.end_date.naive_utc());
+        self.updated_dates
+            .push(v.updated_date.map(|ud| ud.naive_utc()));
+        self.values.push(v.value);
+    }
+
+    fn into_df(self, mut columns: Vec<Series>) -> Result<DataFrame, anyhow::Error> {
+        columns.push(Series::new("start_date".into(), self.start_dates));
+        columns.push(Series::new("end_date".into(), self.end_dates));
+        columns.push(Series::new("updated_date".into(), self.updated_dates));
+        columns.push(Series::new("value".into(), self.values));
+
+        Ok(DataFrame::new(columns)?)
+    }
+}
+
+impl PerProductionTypeResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut production_types: Vec<String> = vec![];
+        let mut columns = ValueColumns::default();
+
+        for generation in &self.actual_generations_per_production_type {
+            for v in &generation.values {
+                production_types.push(generation.production_type.to_string());
+                columns.push(v);
+            }
+        }
+
+        columns.into_df(vec![Series::new(
+            "production_type".into(),
+            production_types,
+        )])
+    }
+}
+
+impl PerUnitResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut eic_codes: Vec<&str> = vec![];
+        let mut names: Vec<&str> = vec![];
+        let mut production_types: Vec<String> = vec![];
+        let mut columns = ValueColumns::default();
+
+        for generation in &self.actual_generations_per_unit {
+            for v in &generation.values {
+                eic_codes.push(&generation.unit.eic_code);
+                names.push(&generation.unit.name);
+                production_types.push(generation.unit.production_type.to_string());
+                columns.push(v);
+            }
+        }
+
+        columns.into_df(vec![
+            Series::new("eic_code".into(), eic_codes),
+            Series::new("name".into(), names),
+            Series::new("production_type".into(), production_types),
+        ])
+    }
+}
+
+impl PerSectorResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut sectors: Vec<&str> = vec![];
+        let mut columns = ValueColumns::default();
+
+        for generation in &self.actual_generations_per_sector {
+            for v in &generation.values {
+                sectors.push(&generation.sector);
+                columns.push(v);
+            }
+        }
+
+        columns.into_df(vec![Series::new("sector".into(), sectors)])
+    }
+}
+
+impl WaterReservesResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut columns = ValueColumns::default();
+
+        for reserves in &self.water_reserves {
+            for v in &reserves.values {
+                columns.push(v);
+            }
+        }
+
+        columns.into_df(vec![])
+    }
+}
+
+impl GenerationMixResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut production_types: Vec<String> = vec![];
+        let mut production_subtypes: Vec<Option<&str>> = vec![];
+        let mut columns = ValueColumns::default();
+
+        for generation in &self.generation_mix_15min_time_scale {
+            for v in &generation.values {
+                production_types.push(generation.production_type.to_string());
+                production_subtypes.push(generation.production_subtype.as_deref());
+                columns.push(v);
+            }
+        }
+
+        columns.into_df(vec![
+            Series::new("production_type".into(), production_types),
+            Series::new("production_subtype".into(), production_subtypes),
+        ])
+    }
+}
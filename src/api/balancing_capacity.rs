@@ -0,0 +1,213 @@
+use core::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::balancing_energy::Direction;
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct BalancingCapacity<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for BalancingCapacity<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Reserve procured ahead of real time
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum ReserveProduct {
+    /// Frequency containment reserve, symmetric
+    Fcr,
+    /// Automatic frequency restoration reserve
+    Afrr,
+    /// Manual frequency restoration reserve
+    Mfrr,
+    /// Replacement reserve
+    Rr,
+    /// A product not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for ReserveProduct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let p = match self {
+            ReserveProduct::Fcr => "FCR",
+            ReserveProduct::Afrr => "AFRR",
+            ReserveProduct::Mfrr => "MFRR",
+            ReserveProduct::Rr => "RR",
+            ReserveProduct::Unknown(p) => p,
+        };
+        write!(f, "{}", p)
+    }
+}
+
+impl From<String> for ReserveProduct {
+    fn from(p: String) -> Self {
+        match p.as_str() {
+            "FCR" => ReserveProduct::Fcr,
+            "AFRR" => ReserveProduct::Afrr,
+            "MFRR" => ReserveProduct::Mfrr,
+            "RR" => ReserveProduct::Rr,
+            _ => ReserveProduct::Unknown(p),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ProcuredReservesResponse {
+    pub procured_reserves: Vec<ReserveSeries>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CapacityPricesResponse {
+    pub capacity_prices: Vec<ReserveSeries>,
+}
+
+/// Values of a reserve product, per contracting period
+#[derive(Deserialize, Debug)]
+pub struct ReserveSeries {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub product: ReserveProduct,
+    /// `None` for symmetric products
+    pub direction: Option<Direction>,
+    pub values: Vec<ReserveValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReserveValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Procured volume in MW, or capacity price in EUR/MW/h
+    pub value: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+impl<'a> BalancingCapacity<'a> {
+    const PROCURED_RESERVES_URL: &'static str = "/open_api/balancing_capacity/v4/procured_reserves";
+    const CAPACITY_PRICES_URL: &'static str = "/open_api/balancing_capacity/v4/capacity_prices";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the procured volumes of each reserve product, in MW
+    pub fn procured_reserves(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<ProcuredReservesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: ProcuredReservesResponse =
+            get_response(self.client, BalancingCapacity::PROCURED_RESERVES_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the capacity prices of each reserve product, in EUR/MW/h
+    pub fn capacity_prices(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<CapacityPricesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: CapacityPricesResponse =
+            get_response(self.client, BalancingCapacity::CAPACITY_PRICES_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+fn unknown_reserves(series: &[ReserveSeries]) -> Vec<String> {
+    let mut unknown_values = vec![];
+    for s in series {
+        if let ReserveProduct::Unknown(p) = &s.product {
+            unknown_values.push(p.clone());
+        }
+        if let Some(Direction::Unknown(d)) = &s.direction {
+            unknown_values.push(d.clone());
+        }
+    }
+    unknown_values
+}
+
+impl UnknownValues for ProcuredReservesResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        unknown_reserves(&self.procured_reserves)
+    }
+}
+
+impl UnknownValues for CapacityPricesResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        unknown_reserves(&self.capacity_prices)
+    }
+}
+
+/// Returns one row per product, direction and interval
+fn series_as_polars_df(
+    series: &[ReserveSeries],
+    value_column: &str,
+) -> Result<DataFrame, anyhow::Error> {
+    let mut products: Vec<String> = vec![];
+    let mut directions: Vec<Option<String>> = vec![];
+    let mut start_dates: Vec<NaiveDateTime> = vec![];
+    let mut end_dates: Vec<NaiveDateTime> = vec![];
+    let mut values: Vec<f64> = vec![];
+    let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+    for s in series {
+        for v in &s.values {
+            products.push(s.product.to_string());
+            directions.push(s.direction.as_ref().map(|d| d.to_string()));
+            start_dates.push(v.start_date.naive_utc());
+            end_dates.push(v.end_date.naive_utc());
+            values.push(v.value);
+            updated_dates.push(v.updated_date.map(|ud| ud.naive_utc()));
+        }
+    }
+
+    let df = DataFrame::new(vec![
+        Series::new("product".into(), products),
+        Series::new("direction".into(), directions),
+        Series::new("start_date".into(), start_dates),
+        Series::new("end_date".into(), end_dates),
+        Series::new(value_column.into(), values),
+        Series::new("updated_date".into(), updated_dates),
+    ])?;
+
+    Ok(df)
+}
+
+impl ProcuredReservesResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        series_as_polars_df(&self.procured_reserves, "volume")
+    }
+}
+
+impl CapacityPricesResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        series_as_polars_df(&self.capacity_prices, "price")
+    }
+}
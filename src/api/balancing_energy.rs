@@ -0,0 +1,411 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct BalancingEnergy<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for BalancingEnergy<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Direction of an activation, seen from the system
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(from = "String")]
+pub enum Direction {
+    /// More generation or less consumption
+    Upward,
+    /// Less generation or more consumption
+    Downward,
+    /// A direction not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = match self {
+            Direction::Upward => "UPWARD",
+            Direction::Downward => "DOWNWARD",
+            Direction::Unknown(d) => d,
+        };
+        write!(f, "{}", d)
+    }
+}
+
+impl From<String> for Direction {
+    fn from(d: String) -> Self {
+        match d.as_str() {
+            "UPWARD" => Direction::Upward,
+            "DOWNWARD" => Direction::Downward,
+            _ => Direction::Unknown(d),
+        }
+    }
+}
+
+/// Balancing product the energy was activated on
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(from = "String")]
+pub enum BalancingProduct {
+    /// Automatic frequency restoration reserve
+    Afrr,
+    /// Manual frequency restoration reserve
+    Mfrr,
+    /// Replacement reserve
+    Rr,
+    /// A product not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for BalancingProduct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let p = match self {
+            BalancingProduct::Afrr => "AFRR",
+            BalancingProduct::Mfrr => "MFRR",
+            BalancingProduct::Rr => "RR",
+            BalancingProduct::Unknown(p) => p,
+        };
+        write!(f, "{}", p)
+    }
+}
+
+impl From<String> for BalancingProduct {
+    fn from(p: String) -> Self {
+        match p.as_str() {
+            "AFRR" => BalancingProduct::Afrr,
+            "MFRR" => BalancingProduct::Mfrr,
+            "RR" => BalancingProduct::Rr,
+            _ => BalancingProduct::Unknown(p),
+        }
+    }
+}
+
+/// Length of the imbalance settlement period: 30 minutes until the move to
+/// 15 minutes at the end of 2024
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementPeriod {
+    FifteenMinutes,
+    ThirtyMinutes,
+}
+
+impl SettlementPeriod {
+    fn duration(&self) -> Duration {
+        match self {
+            SettlementPeriod::FifteenMinutes => Duration::minutes(15),
+            SettlementPeriod::ThirtyMinutes => Duration::minutes(30),
+        }
+    }
+
+    /// Returns the start of the period containing `at`
+    pub fn period_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let seconds = self.duration().num_seconds();
+        let ts = at.timestamp();
+        DateTime::from_timestamp(ts - ts.rem_euclid(seconds), 0).unwrap()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ImbalanceDataResponse {
+    pub imbalance_data: Vec<ImbalanceData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ImbalanceData {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ImbalanceValue>,
+}
+
+/// Imbalance of a settlement period
+#[derive(Deserialize, Debug)]
+pub struct ImbalanceValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// System imbalance in MWh, positive when the system is long
+    pub system_imbalance: Option<f64>,
+    /// Price paid to positive imbalances, in EUR/MWh
+    pub positive_imbalance_settlement_price: Option<f64>,
+    /// Price charged to negative imbalances, in EUR/MWh
+    pub negative_imbalance_settlement_price: Option<f64>,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ActivatedEnergyResponse {
+    pub activated_energy: Vec<ActivatedEnergy>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ActivatedEnergy {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub product: BalancingProduct,
+    pub direction: Direction,
+    pub values: Vec<ActivatedEnergyValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ActivatedEnergyValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Activated energy, in MWh
+    #[serde(rename = "value")]
+    pub volume: f64,
+    /// Average price of the activated energy, in EUR/MWh
+    pub price: Option<f64>,
+}
+
+impl<'a> BalancingEnergy<'a> {
+    const IMBALANCE_URL: &'static str = "/open_api/balancing_energy/v4/imbalance_data";
+    const ACTIVATED_ENERGY_URL: &'static str = "/open_api/balancing_energy/v4/activated_energy";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the system imbalance and the imbalance settlement prices
+    pub fn imbalance(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<ImbalanceDataResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        get_response(self.client, BalancingEnergy::IMBALANCE_URL, &qs)
+    }
+
+    /// Returns the energy activated on each balancing product and direction
+    pub fn activated_energy(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<ActivatedEnergyResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: ActivatedEnergyResponse =
+            get_response(self.client, BalancingEnergy::ACTIVATED_ENERGY_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for ActivatedEnergyResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for ae in &self.activated_energy {
+            if let BalancingProduct::Unknown(p) = &ae.product {
+                unknown_values.push(p.clone());
+            }
+            if let Direction::Unknown(d) = &ae.direction {
+                unknown_values.push(d.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl ImbalanceDataResponse {
+    /// Returns one row per settlement period
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut system_imbalances: Vec<Option<f64>> = vec![];
+        let mut positive_prices: Vec<Option<f64>> = vec![];
+        let mut negative_prices: Vec<Option<f64>> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for data in &self.imbalance_data {
+            for v in &data.values {
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                system_imbalances.push(v.system_imbalance);
+                positive_prices.push(v.positive_imbalance_settlement_price);
+                negative_prices.push(v.negative_imbalance_settlement_price);
+                updated_dates.push(v.updated_date.map(|ud| ud.naive_utc()));
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("system_imbalance".into(), system_imbalances),
+            Series::new("positive_imbalance_price".into(), positive_prices),
+            Series::new("negative_imbalance_price".into(), negative_prices),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
+
+impl ActivatedEnergyResponse {
+    /// Returns one row per product, direction and interval, as published
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut products: Vec<String> = vec![];
+        let mut directions: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut volumes: Vec<f64> = vec![];
+        let mut prices: Vec<Option<f64>> = vec![];
+
+        for ae in &self.activated_energy {
+            for v in &ae.values {
+                products.push(ae.product.to_string());
+                directions.push(ae.direction.to_string());
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                volumes.push(v.volume);
+                prices.push(v.price);
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("product".into(), products),
+            Series::new("direction".into(), directions),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("volume".into(), volumes),
+            Series::new("price".into(), prices),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Returns one row per product, direction and settlement period, summing
+    /// the volumes and averaging the prices weighted by volume. A value
+    /// spanning several periods is split between them in proportion to its
+    /// duration within each of them
+    pub fn settlement_df(
+        &self,
+        period: SettlementPeriod,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        type Key = (String, String, DateTime<Utc>);
+        // volume, price * volume, priced volume
+        let mut rows: BTreeMap<Key, (f64, f64, f64)> = BTreeMap::new();
+
+        for ae in &self.activated_energy {
+            for v in &ae.values {
+                let length = (v.end_date - v.start_date).num_seconds();
+                let mut start_date = period.period_start(v.start_date);
+                loop {
+                    let end_date = start_date + period.duration();
+                    let share = if length > 0 {
+                        let overlap = end_date.min(v.end_date) - start_date.max(v.start_date);
+                        overlap.num_seconds() as f64 / length as f64
+                    } else {
+                        1.0
+                    };
+
+                    let key = (ae.product.to_string(), ae.direction.to_string(), start_date);
+                    let row = rows.entry(key).or_default();
+                    let volume = v.volume * share;
+                    row.0 += volume;
+                    if let Some(price) = v.price {
+                        row.1 += price * volume;
+                        row.2 += volume;
+                    }
+
+                    if end_date >= v.end_date {
+                        break;
+                    }
+                    start_date = end_date;
+                }
+            }
+        }
+
+        let mut products: Vec<String> = vec![];
+        let mut directions: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut volumes: Vec<f64> = vec![];
+        let mut prices: Vec<Option<f64>> = vec![];
+
+        for ((product, direction, start_date), (volume, weighted_price, priced_volume)) in rows {
+            products.push(product);
+            directions.push(direction);
+            start_dates.push(start_date.naive_utc());
+            end_dates.push((start_date + period.duration()).naive_utc());
+            volumes.push(volume);
+            prices.push((priced_volume > 0.0).then(|| weighted_price / priced_volume));
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("product".into(), products),
+            Series::new("direction".into(), directions),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("volume".into(), volumes),
+            Series::new("price".into(), prices),
+        ])?;
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 30 MWh activated at 50 EUR/MWh from 00:10 to 00:40
+    fn activation() -> ActivatedEnergyResponse {
+        serde_json::from_str(
+            r#"{"activated_energy":[{"start_date":"2024-01-01T00:00:00Z","end_date":"2024-01-01T01:00:00Z","product":"AFRR","direction":"UPWARD","values":[
+            {"start_date":"2024-01-01T00:10:00Z","end_date":"2024-01-01T00:40:00Z","value":30.0,"price":50.0}]}]}"#,
+        )
+        .unwrap()
+    }
+
+    fn volumes(df: &DataFrame) -> Vec<f64> {
+        df.column("volume")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn activations_are_split_across_settlement_periods() {
+        let df = activation()
+            .settlement_df(SettlementPeriod::FifteenMinutes)
+            .unwrap();
+
+        assert_eq!(df.height(), 3);
+        let split = volumes(&df);
+        assert_eq!(split, vec![5.0, 15.0, 10.0]);
+        assert_eq!(split.iter().sum::<f64>(), 30.0);
+        let prices: Vec<Option<f64>> = df
+            .column("price")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(prices, vec![Some(50.0); 3]);
+
+        let df = activation()
+            .settlement_df(SettlementPeriod::ThirtyMinutes)
+            .unwrap();
+        assert_eq!(volumes(&df), vec![20.0, 10.0]);
+    }
+}
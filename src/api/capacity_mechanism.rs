@@ -0,0 +1,289 @@
+use core::fmt;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Europe::Paris;
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{ensure_known_values, get_response, intervals, DateRange, StrictMode, UnknownValues};
+
+pub struct CapacityMechanism<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for CapacityMechanism<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Peak period of the capacity mechanism
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum PeakPeriodType {
+    /// Consumption peak days, driving the capacity obligation of suppliers
+    Pp1,
+    /// System stress days, used to certify capacities. PP1 days are always
+    /// PP2 days as well
+    Pp2,
+    /// A peak period not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for PeakPeriodType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pp = match self {
+            PeakPeriodType::Pp1 => "PP1",
+            PeakPeriodType::Pp2 => "PP2",
+            PeakPeriodType::Unknown(pp) => pp,
+        };
+        write!(f, "{}", pp)
+    }
+}
+
+impl From<String> for PeakPeriodType {
+    fn from(pp: String) -> Self {
+        match pp.as_str() {
+            "PP1" => PeakPeriodType::Pp1,
+            "PP2" => PeakPeriodType::Pp2,
+            _ => PeakPeriodType::Unknown(pp),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PeakPeriodsResponse {
+    pub peak_periods: Vec<PeakPeriods>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PeakPeriods {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<PeakDay>,
+}
+
+/// A day designated as a peak day, from midnight to midnight french local time
+#[derive(Deserialize, Debug)]
+pub struct PeakDay {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub ty: PeakPeriodType,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+/// Peak hours of a peak day, in french local time: 07:00 to 15:00 and 18:00
+/// to 20:00
+const PEAK_HOURS: [(u32, u32); 2] = [(7, 15), (18, 20)];
+
+impl PeakDay {
+    /// The calendar day, in french local time
+    pub fn day(&self) -> NaiveDate {
+        self.start_date.with_timezone(&Paris).date_naive()
+    }
+
+    /// Whether this day counts for the given peak period
+    pub fn is(&self, ty: &PeakPeriodType) -> bool {
+        self.ty == *ty || (self.ty == PeakPeriodType::Pp1 && *ty == PeakPeriodType::Pp2)
+    }
+
+    /// Returns the peak hours of the day, as `(start, end)` intervals
+    pub fn hours(&self) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        // Peak hours are never skipped nor repeated by daylight saving changes
+        let at = |hour: u32| {
+            Paris
+                .from_local_datetime(&self.day().and_hms_opt(hour, 0, 0).unwrap())
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        PEAK_HOURS
+            .iter()
+            .map(|(start, end)| (at(*start), at(*end)))
+            .collect()
+    }
+}
+
+impl<'a> CapacityMechanism<'a> {
+    const URL: &'static str = "/open_api/capacity_mechanism/v1/peak_periods";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the PP1 and PP2 days designated in the range. Days are
+    /// announced the day before
+    pub fn peak_periods(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<PeakPeriodsResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: PeakPeriodsResponse = get_response(self.client, CapacityMechanism::URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for PeakPeriodsResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for day in self.peak_periods.iter().flat_map(|pp| &pp.values) {
+            if let PeakPeriodType::Unknown(pp) = &day.ty {
+                unknown_values.push(pp.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl PeakPeriodsResponse {
+    /// Returns the days counting for the given peak period
+    pub fn days(&self, ty: &PeakPeriodType) -> Vec<NaiveDate> {
+        self.peak_periods
+            .iter()
+            .flat_map(|pp| &pp.values)
+            .filter(|day| day.is(ty))
+            .map(|day| day.day())
+            .collect()
+    }
+
+    /// Whether the interval overlaps the peak hours of a day counting for the
+    /// given peak period
+    pub fn is_peak_period(
+        &self,
+        ty: &PeakPeriodType,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> bool {
+        self.peak_periods
+            .iter()
+            .flat_map(|pp| &pp.values)
+            .filter(|day| day.is(ty))
+            .flat_map(|day| day.hours())
+            .any(|(start, end)| start_date < end && start < end_date)
+    }
+
+    /// Returns one row per peak day
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut days: Vec<NaiveDate> = vec![];
+        let mut types: Vec<String> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for day in self.peak_periods.iter().flat_map(|pp| &pp.values) {
+            days.push(day.day());
+            types.push(day.ty.to_string());
+            updated_dates.push(day.updated_date.map(|ud| ud.naive_utc()));
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("day".into(), days),
+            Series::new("type".into(), types),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Adds `is_pp1` and `is_pp2` columns to a consumption frame with
+    /// `start_date` and `end_date` columns, e.g. from
+    /// `ShortTermResponse::as_polars_df`. The flags are joined on the
+    /// intervals, so the frame may be in any order
+    pub fn annotate_consumption(
+        &self,
+        consumption: &DataFrame,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut is_pp1: Vec<bool> = vec![];
+        let mut is_pp2: Vec<bool> = vec![];
+
+        for (start_date, end_date) in intervals(consumption)? {
+            let (start, end) = (start_date.and_utc(), end_date.and_utc());
+            start_dates.push(start_date);
+            end_dates.push(end_date);
+            is_pp1.push(self.is_peak_period(&PeakPeriodType::Pp1, start, end));
+            is_pp2.push(self.is_peak_period(&PeakPeriodType::Pp2, start, end));
+        }
+
+        let flags = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("is_pp1".into(), is_pp1),
+            Series::new("is_pp2".into(), is_pp2),
+        ])?;
+
+        let by = [col("start_date"), col("end_date")];
+        let df = consumption
+            .clone()
+            .lazy()
+            .join(flags.lazy(), by.clone(), by, JoinArgs::new(JoinType::Left))
+            .collect()?;
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consumption_is_annotated_by_interval() {
+        let peak_periods: PeakPeriodsResponse = serde_json::from_str(
+            r#"{"peak_periods":[{"start_date":"2024-01-15T00:00:00+01:00","end_date":"2024-01-16T00:00:00+01:00","values":[
+            {"start_date":"2024-01-15T00:00:00+01:00","end_date":"2024-01-16T00:00:00+01:00","type":"PP2","updated_date":null}]}]}"#,
+        )
+        .unwrap();
+
+        // Unsorted, with a repeated interval and a gap
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap().naive_utc();
+        let consumption = DataFrame::new(vec![
+            Series::new(
+                "start_date".into(),
+                vec![
+                    at("2024-01-15T07:00:00Z"),
+                    at("2024-01-15T02:00:00Z"),
+                    at("2024-01-15T07:00:00Z"),
+                ],
+            ),
+            Series::new(
+                "end_date".into(),
+                vec![
+                    at("2024-01-15T07:30:00Z"),
+                    at("2024-01-15T02:30:00Z"),
+                    at("2024-01-15T07:30:00Z"),
+                ],
+            ),
+            Series::new("value".into(), vec![60000.0, 50000.0, 61000.0]),
+        ])
+        .unwrap();
+
+        let df = peak_periods.annotate_consumption(&consumption).unwrap();
+        let flags = |name: &str| -> Vec<Option<bool>> {
+            df.column(name)
+                .unwrap()
+                .bool()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(flags("is_pp1"), vec![Some(false); 3]);
+        assert_eq!(flags("is_pp2"), vec![Some(true), Some(false), Some(true)]);
+        assert_eq!(
+            df.column("value").unwrap(),
+            consumption.column("value").unwrap()
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::actual_generation::{ActualGeneration, ActualProductionType, PerProductionTypeResponse};
+use super::{get_response, intervals, DateRange, StrictMode};
+
+pub struct Co2Intensity<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for Co2Intensity<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Co2EmissionRatesResponse {
+    pub co2_emission_rates: Vec<Co2EmissionRates>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Co2EmissionRates {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<Co2EmissionRate>,
+}
+
+/// Carbon intensity of the french generation, in gCO2/kWh
+#[derive(Deserialize, Debug)]
+pub struct Co2EmissionRate {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub value: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+/// Emission factors per production type, in gCO2/kWh. Production types
+/// without a factor are considered carbon free
+#[derive(Debug, Clone)]
+pub struct EmissionFactors {
+    factors: Vec<(ActualProductionType, f64)>,
+}
+
+impl Default for EmissionFactors {
+    /// The factors used by RTE for eCO2mix
+    fn default() -> Self {
+        Self {
+            factors: vec![
+                (ActualProductionType::FossilHardCoal, 986.0),
+                (ActualProductionType::FossilOil, 777.0),
+                (ActualProductionType::FossilGas, 429.0),
+                (ActualProductionType::Biomass, 494.0),
+                (ActualProductionType::Bioenergy, 494.0),
+                (ActualProductionType::Waste, 494.0),
+            ],
+        }
+    }
+}
+
+impl EmissionFactors {
+    /// Sets the factor of a production type, replacing the previous one
+    pub fn with_factor(mut self, production_type: ActualProductionType, factor: f64) -> Self {
+        self.factors.retain(|(pt, _)| *pt != production_type);
+        self.factors.push((production_type, factor));
+        self
+    }
+
+    pub fn factor(&self, production_type: &ActualProductionType) -> f64 {
+        self.factors
+            .iter()
+            .find(|(pt, _)| pt == production_type)
+            .map_or(0.0, |(_, factor)| *factor)
+    }
+}
+
+impl<'a> Co2Intensity<'a> {
+    const URL: &'static str = "/open_api/co2_emissions/v1/co2_emission_rates";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the CO2 emission rate published by RTE
+    pub fn emission_rates(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<Co2EmissionRatesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        get_response(self.client, Co2Intensity::URL, &qs)
+    }
+
+    /// Computes the emission rate from the actual generation per production
+    /// type, for when the published one is missing
+    pub fn computed_emission_rates(
+        &self,
+        factors: &EmissionFactors,
+        date_range: Option<DateRange>,
+    ) -> Result<Co2EmissionRatesResponse, anyhow::Error> {
+        let generation = ActualGeneration::new(self.client)
+            .with_strict_mode(self.strict)
+            .per_production_type(date_range)?;
+
+        Ok(Co2EmissionRatesResponse::from_generation(
+            &generation,
+            factors,
+        ))
+    }
+}
+
+impl Co2EmissionRatesResponse {
+    /// Weights the emission factors by the generation of each production type.
+    /// Consumption, exchanges and pumping are left out
+    pub fn from_generation(
+        generation: &PerProductionTypeResponse,
+        factors: &EmissionFactors,
+    ) -> Self {
+        // emissions, generation
+        let mut intervals: BTreeMap<(DateTime<Utc>, DateTime<Utc>), (f64, f64)> = BTreeMap::new();
+
+        for per_type in &generation.actual_generations_per_production_type {
+            if matches!(
+                per_type.production_type,
+                ActualProductionType::Consumption
+                    | ActualProductionType::Exchange
+                    | ActualProductionType::Pumping
+            ) {
+                continue;
+            }
+            let factor = factors.factor(&per_type.production_type);
+            for v in &per_type.values {
+                let value = v.value.max(0.0);
+                let interval = intervals.entry((v.start_date, v.end_date)).or_default();
+                interval.0 += factor * value;
+                interval.1 += value;
+            }
+        }
+
+        let values: Vec<Co2EmissionRate> = intervals
+            .into_iter()
+            .filter(|(_, (_, total))| *total > 0.0)
+            .map(
+                |((start_date, end_date), (emissions, total))| Co2EmissionRate {
+                    start_date,
+                    end_date,
+                    value: emissions / total,
+                    updated_date: None,
+                },
+            )
+            .collect();
+
+        let co2_emission_rates = match (values.first(), values.last()) {
+            (Some(first), Some(last)) => vec![Co2EmissionRates {
+                start_date: first.start_date,
+                end_date: last.end_date,
+                values,
+            }],
+            _ => vec![],
+        };
+
+        Self { co2_emission_rates }
+    }
+
+    /// Returns the emission rate in force at a given time
+    pub fn rate_at(&self, at: DateTime<Utc>) -> Option<f64> {
+        self.co2_emission_rates
+            .iter()
+            .flat_map(|rates| &rates.values)
+            .find(|rate| rate.start_date <= at && at < rate.end_date)
+            .map(|rate| rate.value)
+    }
+
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut values: Vec<f64> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for rate in self
+            .co2_emission_rates
+            .iter()
+            .flat_map(|rates| &rates.values)
+        {
+            start_dates.push(rate.start_date.naive_utc());
+            end_dates.push(rate.end_date.naive_utc());
+            values.push(rate.value);
+            updated_dates.push(rate.updated_date.map(|ud| ud.naive_utc()));
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("value".into(), values),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Attaches the emission rate in force at the start of each interval to a
+    /// consumption frame with `start_date`, `end_date` and `value` columns,
+    /// e.g. from `ShortTermResponse::as_polars_df`, as `co2_intensity`, along
+    /// with the resulting `co2_emissions` in tCO2/h. The rates are joined on
+    /// the intervals, so the frame may be in any order
+    pub fn join_consumption(
+        &self,
+        consumption: &DataFrame,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut intensities: Vec<Option<f64>> = vec![];
+
+        for (start_date, end_date) in intervals(consumption)? {
+            start_dates.push(start_date);
+            end_dates.push(end_date);
+            intensities.push(self.rate_at(start_date.and_utc()));
+        }
+
+        let rates = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("co2_intensity".into(), intensities),
+        ])?;
+
+        let by = [col("start_date"), col("end_date")];
+        let df = consumption
+            .clone()
+            .lazy()
+            .join(rates.lazy(), by.clone(), by, JoinArgs::new(JoinType::Left))
+            // MW * gCO2/kWh = kgCO2/h
+            .with_column((col("value") * col("co2_intensity") / lit(1000.0)).alias("co2_emissions"))
+            .collect()?;
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn rates_are_joined_on_the_consumption_intervals() {
+        let rates = Co2EmissionRatesResponse {
+            co2_emission_rates: vec![Co2EmissionRates {
+                start_date: at("2024-01-15T00:00:00Z"),
+                end_date: at("2024-01-15T02:00:00Z"),
+                values: vec![
+                    Co2EmissionRate {
+                        start_date: at("2024-01-15T00:00:00Z"),
+                        end_date: at("2024-01-15T01:00:00Z"),
+                        value: 40.0,
+                        updated_date: None,
+                    },
+                    Co2EmissionRate {
+                        start_date: at("2024-01-15T01:00:00Z"),
+                        end_date: at("2024-01-15T02:00:00Z"),
+                        value: 60.0,
+                        updated_date: None,
+                    },
+                ],
+            }],
+        };
+
+        // Unsorted, and partly outside of the published rates
+        let consumption = DataFrame::new(vec![
+            Series::new(
+                "start_date".into(),
+                vec![
+                    at("2024-01-15T01:00:00Z").naive_utc(),
+                    at("2024-01-15T05:00:00Z").naive_utc(),
+                    at("2024-01-15T00:00:00Z").naive_utc(),
+                ],
+            ),
+            Series::new(
+                "end_date".into(),
+                vec![
+                    at("2024-01-15T01:30:00Z").naive_utc(),
+                    at("2024-01-15T05:30:00Z").naive_utc(),
+                    at("2024-01-15T00:30:00Z").naive_utc(),
+                ],
+            ),
+            Series::new("value".into(), vec![50000.0, 55000.0, 45000.0]),
+        ])
+        .unwrap();
+
+        let df = rates.join_consumption(&consumption).unwrap();
+        let column = |name: &str| -> Vec<Option<f64>> {
+            df.column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(column("co2_intensity"), vec![Some(60.0), None, Some(40.0)]);
+        assert_eq!(
+            column("co2_emissions"),
+            vec![Some(3000.0), None, Some(1800.0)]
+        );
+    }
+
+    #[test]
+    fn unknown_production_types_are_carbon_free_generation() {
+        let generation: PerProductionTypeResponse = serde_json::from_str(
+            r#"{"actual_generations_per_production_type":[
+            {"production_type":"FOSSIL_GAS","start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","values":[
+                {"start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","updated_date":null,"value":100.0}]},
+            {"production_type":"NUCLEAR","start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","values":[
+                {"start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","updated_date":null,"value":300.0}]},
+            {"production_type":"GEOTHERMAL","start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","values":[
+                {"start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","updated_date":null,"value":100.0}]},
+            {"production_type":"CONSUMPTION","start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","values":[
+                {"start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","updated_date":null,"value":1000.0}]}]}"#,
+        )
+        .unwrap();
+        let geothermal = ActualProductionType::Unknown("GEOTHERMAL".to_string());
+
+        let factors = EmissionFactors::default();
+        assert_eq!(factors.factor(&geothermal), 0.0);
+        let rates = Co2EmissionRatesResponse::from_generation(&generation, &factors);
+        // 100 MW of gas out of 500 MW generated
+        assert_eq!(rates.rate_at(at("2024-01-15T00:30:00Z")), Some(429.0 / 5.0));
+
+        let factors = factors.with_factor(geothermal, 38.0);
+        let rates = Co2EmissionRatesResponse::from_generation(&generation, &factors);
+        assert_eq!(
+            rates.rate_at(at("2024-01-15T00:30:00Z")),
+            Some((429.0 * 100.0 + 38.0 * 100.0) / 500.0)
+        );
+    }
+}
@@ -1,20 +1,29 @@
+use std::collections::BTreeMap;
 use std::fmt;
 
 use crate::ApiClient;
 use anyhow::Ok;
 use polars::prelude::*;
 use serde::Deserialize;
-use serde_json;
 
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 
-use super::DateRange;
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
 
 pub struct ConsumptionForecast<'a> {
     client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for ConsumptionForecast<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
 }
 
 /// The type of forecast to retrieve
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
 pub enum ShortTermForecastType {
     /// Realised consumption, not a forecast then
     Realised,
@@ -27,6 +36,9 @@ pub enum ShortTermForecastType {
 
     /// Day after tomorrow forecast
     DayAfterTomorrow,
+
+    /// A forecast type not (yet) known by this crate
+    Unknown(String),
 }
 
 impl fmt::Display for ShortTermForecastType {
@@ -36,11 +48,24 @@ impl fmt::Display for ShortTermForecastType {
             ShortTermForecastType::Intraday => "ID",
             ShortTermForecastType::Tomorrow => "D-1",
             ShortTermForecastType::DayAfterTomorrow => "D-2",
+            ShortTermForecastType::Unknown(ft) => ft,
         };
         write!(f, "{}", ft)
     }
 }
 
+impl From<String> for ShortTermForecastType {
+    fn from(ft: String) -> Self {
+        match ft.as_str() {
+            "REALISED" => ShortTermForecastType::Realised,
+            "ID" => ShortTermForecastType::Intraday,
+            "D-1" => ShortTermForecastType::Tomorrow,
+            "D-2" => ShortTermForecastType::DayAfterTomorrow,
+            _ => ShortTermForecastType::Unknown(ft),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ShortTermResponse {
     pub short_term: Vec<ShortTerm>,
@@ -49,7 +74,7 @@ pub struct ShortTermResponse {
 #[derive(Deserialize, Debug)]
 pub struct ShortTerm {
     #[serde(rename = "type")]
-    pub ty: String,
+    pub ty: ShortTermForecastType,
 
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
@@ -94,12 +119,46 @@ pub struct WeeklyForecastValue {
     pub value: f64,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct AnnualForecastResponse {
+    pub annual_forecasts: Vec<AnnualForecast>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AnnualForecast {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub updated_date: DateTime<Utc>,
+    pub values: Vec<AnnualForecastValue>,
+}
+
+/// Weekly consumption forecast over the year
+#[derive(Deserialize, Debug)]
+pub struct AnnualForecastValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Average consumption from saturday to friday
+    pub average_load_saturday_to_friday: f64,
+    /// Average consumption from monday to sunday
+    pub average_load_monday_to_sunday: f64,
+    /// Minimum consumption of the week
+    pub weekly_minimum: f64,
+    /// Maximum (peak) consumption of the week
+    pub weekly_maximum: f64,
+    /// Available margin at the peak
+    pub margin: f64,
+}
+
 impl<'a> ConsumptionForecast<'a> {
     const SHORT_TERM_URL: &'static str = "/open_api/consumption/v1/short_term";
     const WEEKLY_URL: &'static str = "/open_api/consumption/v1/weekly_forecasts";
+    const ANNUAL_URL: &'static str = "/open_api/consumption/v1/annual_forecasts";
 
     pub fn new(client: &'a dyn ApiClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            strict: false,
+        }
     }
 
     /// Returns a short term forecast given the forecast type
@@ -107,33 +166,80 @@ impl<'a> ConsumptionForecast<'a> {
         &self,
         forecast_type: ShortTermForecastType,
         date_range: Option<DateRange>,
+    ) -> Result<ShortTermResponse, anyhow::Error> {
+        self.short_terms(&[forecast_type], date_range)
+    }
+
+    /// Returns the short term forecasts of several forecast types in a single
+    /// request. An empty slice lets RTE return every type it has.
+    /// Use `ShortTermResponse::pivot_by_type` to get them aligned on a shared
+    /// time index
+    pub fn short_terms(
+        &self,
+        forecast_types: &[ShortTermForecastType],
+        date_range: Option<DateRange>,
     ) -> Result<ShortTermResponse, anyhow::Error> {
         let mut qs: Vec<(String, String)> = vec![];
 
-        qs.push(("type".to_string(), forecast_type.to_string()));
+        let mut types: Vec<String> = vec![];
+        for forecast_type in forecast_types {
+            let ft = forecast_type.to_string();
+            if !types.contains(&ft) {
+                types.push(ft);
+            }
+        }
+        if !types.is_empty() {
+            qs.push(("type".to_string(), types.join(",")));
+        }
 
         if let Some(date_range) = date_range {
             qs.append(&mut date_range.to_query_string());
         }
 
-        let response = self
-            .client
-            .http_get(ConsumptionForecast::SHORT_TERM_URL, &qs);
+        let res: ShortTermResponse =
+            get_response(self.client, ConsumptionForecast::SHORT_TERM_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
 
-        let reply = response.unwrap();
+    /// Same as `short_terms`, but directly returns the frame with one value
+    /// column per requested forecast type
+    pub fn short_terms_df(
+        &self,
+        forecast_types: &[ShortTermForecastType],
+        date_range: Option<DateRange>,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        self.short_terms(forecast_types, date_range)?
+            .pivot_by_type()
+    }
 
-        let res = serde_json::from_str(&reply);
-        if let Err(e) = res {
-            eprintln!(
-                "Error: parsing reply of {}?{:?} => '{:?}': {:?}",
-                ConsumptionForecast::SHORT_TERM_URL,
-                qs,
-                reply,
-                e
-            );
-            return Err(anyhow::Error::msg("Failed to parse response"));
+    /// Returns how the forecast of each target interval evolved across the
+    /// horizons, from D-2 to ID, along with the REALISED consumption where
+    /// already available
+    pub fn horizons(
+        &self,
+        date_range: DateRange,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let forecast_types = [
+            ShortTermForecastType::DayAfterTomorrow,
+            ShortTermForecastType::Tomorrow,
+            ShortTermForecastType::Intraday,
+            ShortTermForecastType::Realised,
+        ];
+        let df = self.short_terms_df(&forecast_types, Some(date_range))?;
+
+        // RTE returns the types in its own order, and omits the ones without data
+        let mut columns = vec!["start_date".to_string(), "end_date".to_string()];
+        for forecast_type in &forecast_types {
+            let column = forecast_type.to_string();
+            if df.get_column_index(&column).is_some() {
+                columns.push(column);
+            }
         }
-        Ok(res.unwrap())
+
+        Ok(df.select(columns)?)
     }
 
     pub fn weekly_forecast(
@@ -145,50 +251,64 @@ impl<'a> ConsumptionForecast<'a> {
             qs.append(&mut date_range.to_query_string());
         }
 
-        let response = self.client.http_get(ConsumptionForecast::WEEKLY_URL, &qs);
+        get_response(self.client, ConsumptionForecast::WEEKLY_URL, &qs)
+    }
+
+    /// Returns the yearly forecasts of weekly peak and average consumption
+    pub fn annual_forecast(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<AnnualForecastResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
 
-        let reply = response.unwrap();
+        get_response(self.client, ConsumptionForecast::ANNUAL_URL, &qs)
+    }
+}
 
-        let res = serde_json::from_str(&reply);
-        if let Err(e) = res {
-            eprintln!(
-                "Error: parsing reply of {}?{:?} => '{:?}': {:?}",
-                ConsumptionForecast::WEEKLY_URL,
-                qs,
-                reply,
-                e
-            );
-            return Err(anyhow::Error::msg("Failed to parse response"));
+impl UnknownValues for ShortTermResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for short_term in &self.short_term {
+            if let ShortTermForecastType::Unknown(ft) = &short_term.ty {
+                unknown_values.push(ft.clone());
+            }
         }
-        Ok(res.unwrap())
+        unknown_values
     }
 }
 
 // XXX trait
 impl ShortTermResponse {
+    /// Returns all the series of the response in a long format frame, with a
+    /// `type` column telling which forecast type each row belongs to
     pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut types: Vec<String> = vec![];
         let mut start_dates: Vec<NaiveDateTime> = vec![];
         let mut end_dates: Vec<NaiveDateTime> = vec![];
         let mut updated_dates: Vec<NaiveDateTime> = vec![];
         let mut values: Vec<f64> = vec![];
 
-        let short_term_response = &self.short_term[0];
-
-        for st in short_term_response.values.iter() {
-            start_dates.push(st.start_date.naive_utc());
-            end_dates.push(st.end_date.naive_utc());
-            //if let Some(ud) = &st.updated_date {
-            updated_dates.push(st.updated_date.naive_utc());
-            // }
-            values.push(st.value);
+        for short_term in &self.short_term {
+            for st in short_term.values.iter() {
+                types.push(short_term.ty.to_string());
+                start_dates.push(st.start_date.naive_utc());
+                end_dates.push(st.end_date.naive_utc());
+                updated_dates.push(st.updated_date.naive_utc());
+                values.push(st.value);
+            }
         }
 
+        let types_series = Series::new("type".into(), types);
         let start_dates_series = Series::new("start_date".into(), start_dates);
         let end_dates_series = Series::new("end_date".into(), end_dates);
         let updated_dates_series = Series::new("updated_date".into(), updated_dates);
         let values_series = Series::new("value".into(), values);
 
         let df = DataFrame::new(vec![
+            types_series,
             start_dates_series,
             end_dates_series,
             updated_dates_series,
@@ -197,9 +317,48 @@ impl ShortTermResponse {
 
         Ok(df)
     }
+
+    /// Returns a wide frame with one value column per forecast type, sharing
+    /// the same `start_date`/`end_date` time index. Intervals missing from a
+    /// given type are left null
+    pub fn pivot_by_type(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut types: Vec<&ShortTermForecastType> = vec![];
+        for short_term in &self.short_term {
+            if !types.contains(&&short_term.ty) {
+                types.push(&short_term.ty);
+            }
+        }
+
+        let mut rows: BTreeMap<(NaiveDateTime, NaiveDateTime), Vec<Option<f64>>> = BTreeMap::new();
+        for short_term in &self.short_term {
+            let i = types.iter().position(|ty| **ty == short_term.ty).unwrap();
+            for st in short_term.values.iter() {
+                let key = (st.start_date.naive_utc(), st.end_date.naive_utc());
+                rows.entry(key).or_insert_with(|| vec![None; types.len()])[i] = Some(st.value);
+            }
+        }
+
+        let start_dates: Vec<NaiveDateTime> = rows.keys().map(|(start, _)| *start).collect();
+        let end_dates: Vec<NaiveDateTime> = rows.keys().map(|(_, end)| *end).collect();
+
+        let mut columns = vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+        ];
+        for (i, ty) in types.iter().enumerate() {
+            let values: Vec<Option<f64>> = rows.values().map(|v| v[i]).collect();
+            columns.push(Series::new(ty.to_string().into(), values));
+        }
+
+        let df = DataFrame::new(columns)?;
+
+        Ok(df)
+    }
 }
 
 impl WeeklyForecastResponse {
+    /// Returns the half-hourly forecast values. The `is_peak` column flags the
+    /// row containing the forecast peak hour of its day
     pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
         let mut start_dates: Vec<NaiveDateTime> = vec![];
         let mut end_dates: Vec<NaiveDateTime> = vec![];
@@ -207,10 +366,12 @@ impl WeeklyForecastResponse {
         let mut peak_temperatures: Vec<f64> = vec![];
         let mut peak_temperature_deviations: Vec<f64> = vec![];
         let mut values: Vec<f64> = vec![];
+        let mut is_peaks: Vec<bool> = vec![];
 
         for day_forecast in &self.weekly_forecasts {
             let temperature = day_forecast.peak.temperature;
             let temperature_deviation = day_forecast.peak.temperature_deviation;
+            let peak_hour = day_forecast.peak.peak_hour;
 
             for wf in day_forecast.values.iter() {
                 start_dates.push(wf.start_date.naive_utc());
@@ -219,6 +380,7 @@ impl WeeklyForecastResponse {
                 peak_temperatures.push(temperature);
                 peak_temperature_deviations.push(temperature_deviation);
                 values.push(wf.value);
+                is_peaks.push(wf.start_date <= peak_hour && peak_hour < wf.end_date);
             }
         }
 
@@ -238,6 +400,93 @@ impl WeeklyForecastResponse {
             peak_temperatures_series,
             peak_temperature_deviations_series,
             Series::new("value".into(), values),
+            Series::new("is_peak".into(), is_peaks),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Returns the peak forecast details, one row per forecast day
+    pub fn peaks_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut days: Vec<NaiveDate> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut peak_hours: Vec<NaiveDateTime> = vec![];
+        let mut peak_values: Vec<f64> = vec![];
+        let mut temperatures: Vec<f64> = vec![];
+        let mut temperature_deviations: Vec<f64> = vec![];
+        let mut updated_dates: Vec<NaiveDateTime> = vec![];
+
+        for day_forecast in &self.weekly_forecasts {
+            // The day boundaries are in french local time, so take the middle
+            // of the day to get the right date once converted to UTC
+            let middle =
+                day_forecast.start_date + (day_forecast.end_date - day_forecast.start_date) / 2;
+
+            days.push(middle.date_naive());
+            start_dates.push(day_forecast.start_date.naive_utc());
+            end_dates.push(day_forecast.end_date.naive_utc());
+            peak_hours.push(day_forecast.peak.peak_hour.naive_utc());
+            peak_values.push(day_forecast.peak.value);
+            temperatures.push(day_forecast.peak.temperature);
+            temperature_deviations.push(day_forecast.peak.temperature_deviation);
+            updated_dates.push(day_forecast.updated_date.naive_utc());
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("day".into(), days),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("peak_hour".into(), peak_hours),
+            Series::new("peak_value".into(), peak_values),
+            Series::new("temperature".into(), temperatures),
+            Series::new("temperature_deviation".into(), temperature_deviations),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
+
+impl AnnualForecastResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut updated_dates: Vec<NaiveDateTime> = vec![];
+        let mut averages_saturday_to_friday: Vec<f64> = vec![];
+        let mut averages_monday_to_sunday: Vec<f64> = vec![];
+        let mut weekly_minimums: Vec<f64> = vec![];
+        let mut weekly_maximums: Vec<f64> = vec![];
+        let mut margins: Vec<f64> = vec![];
+
+        for year_forecast in &self.annual_forecasts {
+            for af in year_forecast.values.iter() {
+                start_dates.push(af.start_date.naive_utc());
+                end_dates.push(af.end_date.naive_utc());
+                updated_dates.push(year_forecast.updated_date.naive_utc());
+                averages_saturday_to_friday.push(af.average_load_saturday_to_friday);
+                averages_monday_to_sunday.push(af.average_load_monday_to_sunday);
+                weekly_minimums.push(af.weekly_minimum);
+                weekly_maximums.push(af.weekly_maximum);
+                margins.push(af.margin);
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("updated_date".into(), updated_dates),
+            Series::new(
+                "average_load_saturday_to_friday".into(),
+                averages_saturday_to_friday,
+            ),
+            Series::new(
+                "average_load_monday_to_sunday".into(),
+                averages_monday_to_sunday,
+            ),
+            Series::new("weekly_minimum".into(), weekly_minimums),
+            Series::new("weekly_maximum".into(), weekly_maximums),
+            Series::new("margin".into(), margins),
         ])?;
 
         Ok(df)
@@ -0,0 +1,255 @@
+use core::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct DemandResponse<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for DemandResponse<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Stage of a NEBEF curtailment volume
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum NebefVolumeType {
+    /// Curtailment declared by the operators ahead of delivery
+    Programmed,
+    /// Curtailment measured after delivery
+    Achieved,
+    /// A volume type not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for NebefVolumeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            NebefVolumeType::Programmed => "PROGRAMMED",
+            NebefVolumeType::Achieved => "ACHIEVED",
+            NebefVolumeType::Unknown(t) => t,
+        };
+        write!(f, "{}", t)
+    }
+}
+
+impl From<String> for NebefVolumeType {
+    fn from(t: String) -> Self {
+        match t.as_str() {
+            "PROGRAMMED" => NebefVolumeType::Programmed,
+            "ACHIEVED" => NebefVolumeType::Achieved,
+            _ => NebefVolumeType::Unknown(t),
+        }
+    }
+}
+
+/// Channel through which a curtailment was activated
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum SignalType {
+    /// Curtailment sold on the energy markets (NEBEF)
+    Nebef,
+    /// Curtailment activated by RTE on the balancing mechanism
+    BalancingMechanism,
+    /// A signal type not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for SignalType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = match self {
+            SignalType::Nebef => "NEBEF",
+            SignalType::BalancingMechanism => "BALANCING_MECHANISM",
+            SignalType::Unknown(t) => t,
+        };
+        write!(f, "{}", t)
+    }
+}
+
+impl From<String> for SignalType {
+    fn from(t: String) -> Self {
+        match t.as_str() {
+            "NEBEF" => SignalType::Nebef,
+            "BALANCING_MECHANISM" => SignalType::BalancingMechanism,
+            _ => SignalType::Unknown(t),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NebefVolumesResponse {
+    pub nebef_volumes: Vec<NebefVolumes>,
+}
+
+/// Curtailed volumes summed over all the demand response operators
+#[derive(Deserialize, Debug)]
+pub struct NebefVolumes {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub ty: NebefVolumeType,
+    pub values: Vec<NebefVolumeValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NebefVolumeValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Curtailed energy, in MWh
+    pub value: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SignalsResponse {
+    pub signals: Vec<DemandResponseSignal>,
+}
+
+/// A period during which curtailments were activated
+#[derive(Deserialize, Debug)]
+pub struct DemandResponseSignal {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub ty: SignalType,
+    /// Activated power, in MW
+    pub value: Option<f64>,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+impl<'a> DemandResponse<'a> {
+    const NEBEF_VOLUMES_URL: &'static str = "/open_api/demand_response/v1/nebef_volumes";
+    const SIGNALS_URL: &'static str = "/open_api/demand_response/v1/signals";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the programmed and achieved NEBEF curtailments
+    pub fn nebef_volumes(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<NebefVolumesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: NebefVolumesResponse =
+            get_response(self.client, DemandResponse::NEBEF_VOLUMES_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the curtailment activation signals
+    pub fn signals(&self, date_range: Option<DateRange>) -> Result<SignalsResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: SignalsResponse = get_response(self.client, DemandResponse::SIGNALS_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for NebefVolumesResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for volumes in &self.nebef_volumes {
+            if let NebefVolumeType::Unknown(t) = &volumes.ty {
+                unknown_values.push(t.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl UnknownValues for SignalsResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for signal in &self.signals {
+            if let SignalType::Unknown(t) = &signal.ty {
+                unknown_values.push(t.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl NebefVolumesResponse {
+    /// Returns one row per volume type and interval
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut types: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut values: Vec<f64> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for volumes in &self.nebef_volumes {
+            for v in &volumes.values {
+                types.push(volumes.ty.to_string());
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                values.push(v.value);
+                updated_dates.push(v.updated_date.map(|ud| ud.naive_utc()));
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("type".into(), types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("value".into(), values),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
+
+impl SignalsResponse {
+    /// Returns one row per signal
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut types: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut values: Vec<Option<f64>> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for signal in &self.signals {
+            types.push(signal.ty.to_string());
+            start_dates.push(signal.start_date.naive_utc());
+            end_dates.push(signal.end_date.naive_utc());
+            values.push(signal.value);
+            updated_dates.push(signal.updated_date.map(|ud| ud.naive_utc()));
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("type".into(), types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("value".into(), values),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
@@ -0,0 +1,269 @@
+use core::fmt;
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Europe::Paris;
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{ensure_known_values, get_response, StrictMode, UnknownValues};
+
+pub struct Ecowatt<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for Ecowatt<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Grid stress level of the Ecowatt signal
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "u8")]
+pub enum EcowattLevel {
+    /// Pas d'alerte, production décarbonée
+    CarbonFree,
+    /// Pas d'alerte
+    Green,
+    /// Système électrique tendu, écogestes bienvenus
+    Orange,
+    /// Système électrique très tendu, coupures inévitables si pas de baisse de consommation
+    Red,
+    /// A level not (yet) known by this crate
+    Unknown(u8),
+}
+
+impl EcowattLevel {
+    /// Whether consumption should be reduced (orange or red signal)
+    pub fn is_alert(&self) -> bool {
+        matches!(self, EcowattLevel::Orange | EcowattLevel::Red)
+    }
+}
+
+impl fmt::Display for EcowattLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcowattLevel::CarbonFree => write!(f, "CARBON_FREE"),
+            EcowattLevel::Green => write!(f, "GREEN"),
+            EcowattLevel::Orange => write!(f, "ORANGE"),
+            EcowattLevel::Red => write!(f, "RED"),
+            EcowattLevel::Unknown(level) => write!(f, "{}", level),
+        }
+    }
+}
+
+impl From<u8> for EcowattLevel {
+    fn from(level: u8) -> Self {
+        match level {
+            0 => EcowattLevel::CarbonFree,
+            1 => EcowattLevel::Green,
+            2 => EcowattLevel::Orange,
+            3 => EcowattLevel::Red,
+            _ => EcowattLevel::Unknown(level),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SignalsResponse {
+    pub signals: Vec<EcowattDay>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EcowattDay {
+    /// When the signal was published
+    #[serde(rename = "GenerationFichier")]
+    pub generated_date: DateTime<Utc>,
+    /// Start of the day, in french local time
+    #[serde(rename = "jour")]
+    pub day: DateTime<Utc>,
+    /// Level of the whole day
+    #[serde(rename = "dvalue")]
+    pub level: EcowattLevel,
+    pub message: String,
+    pub values: Vec<EcowattHour>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EcowattHour {
+    /// Hour of the french local clock, from 0 to 23
+    #[serde(rename = "pas")]
+    pub hour: u32,
+    #[serde(rename = "hvalue")]
+    pub level: EcowattLevel,
+}
+
+impl<'a> Ecowatt<'a> {
+    const URL: &'static str = "/open_api/ecowatt/v5/signals";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the signals of today and the next three days.
+    /// RTE only allows one call every 15 minutes on this endpoint
+    pub fn signals(&self) -> Result<SignalsResponse, anyhow::Error> {
+        let res: SignalsResponse = get_response(self.client, Ecowatt::URL, &[])?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for SignalsResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for day in &self.signals {
+            let levels = std::iter::once(&day.level).chain(day.values.iter().map(|h| &h.level));
+            for level in levels {
+                if let EcowattLevel::Unknown(level) = level {
+                    unknown_values.push(level.to_string());
+                }
+            }
+        }
+        unknown_values
+    }
+}
+
+impl SignalsResponse {
+    /// Returns one row per hour, with the level of the hour and of its day
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut levels: Vec<String> = vec![];
+        let mut day_levels: Vec<String> = vec![];
+        let mut is_alerts: Vec<bool> = vec![];
+        let mut generated_dates: Vec<NaiveDateTime> = vec![];
+
+        for day in &self.signals {
+            let local_day = day.day.with_timezone(&Paris).date_naive();
+            for hour in &day.values {
+                // `pas` is the hour of the french local clock: the hour skipped
+                // when switching to summer time has no row, and the level of
+                // the repeated hour applies to both of its occurrences when
+                // switching back to winter time
+                let local_start = local_day
+                    .and_hms_opt(hour.hour, 0, 0)
+                    .ok_or_else(|| anyhow::Error::msg(format!("Invalid hour {}", hour.hour)))?;
+                let hour_starts = match Paris.from_local_datetime(&local_start) {
+                    LocalResult::Single(start_date) => vec![start_date],
+                    LocalResult::Ambiguous(first, second) => vec![first, second],
+                    LocalResult::None => vec![],
+                };
+
+                for start_date in hour_starts {
+                    let start_date = start_date.with_timezone(&Utc);
+                    start_dates.push(start_date.naive_utc());
+                    end_dates.push((start_date + Duration::hours(1)).naive_utc());
+                    levels.push(hour.level.to_string());
+                    day_levels.push(day.level.to_string());
+                    is_alerts.push(hour.level.is_alert());
+                    generated_dates.push(day.generated_date.naive_utc());
+                }
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("level".into(), levels),
+            Series::new("day_level".into(), day_levels),
+            Series::new("is_alert".into(), is_alerts),
+            Series::new("generated_date".into(), generated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A day of 24 `pas`, orange at 02:00 and green otherwise
+    fn signals(day: &str) -> SignalsResponse {
+        let hours: Vec<String> = (0..24)
+            .map(|pas| {
+                let level = if pas == 2 { 2 } else { 1 };
+                format!(r#"{{"pas":{pas},"hvalue":{level}}}"#)
+            })
+            .collect();
+        let reply = format!(
+            r#"{{"signals":[{{"GenerationFichier":"{day}","jour":"{day}","dvalue":2,"message":"","values":[{}]}}]}}"#,
+            hours.join(",")
+        );
+        serde_json::from_str(&reply).unwrap()
+    }
+
+    fn column(df: &DataFrame, name: &str) -> Vec<DateTime<Utc>> {
+        df.column(name)
+            .unwrap()
+            .datetime()
+            .unwrap()
+            .as_datetime_iter()
+            .map(|d| d.unwrap().and_utc())
+            .collect()
+    }
+
+    fn levels(df: &DataFrame) -> Vec<&str> {
+        df.column("level")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect()
+    }
+
+    fn assert_consecutive_hours(df: &DataFrame, first: &str, last_end: &str) {
+        let starts = column(df, "start_date");
+        let ends = column(df, "end_date");
+        assert_eq!(starts[0], first.parse::<DateTime<Utc>>().unwrap());
+        assert_eq!(
+            ends[ends.len() - 1],
+            last_end.parse::<DateTime<Utc>>().unwrap()
+        );
+        for (start, end) in starts.iter().zip(&ends) {
+            assert_eq!(*end - *start, Duration::hours(1));
+        }
+        for (end, next_start) in ends.iter().zip(&starts[1..]) {
+            assert_eq!(end, next_start);
+        }
+    }
+
+    #[test]
+    fn spring_forward_day_has_23_hours() {
+        let df = signals("2024-03-31T00:00:00+01:00").as_polars_df().unwrap();
+
+        assert_eq!(df.height(), 23);
+        // 00:00 and 01:00 are in winter time, 03:00 onwards in summer time
+        assert_consecutive_hours(&df, "2024-03-30T23:00:00Z", "2024-03-31T22:00:00Z");
+        assert!(levels(&df).iter().all(|level| *level == "GREEN"));
+    }
+
+    #[test]
+    fn fall_back_day_has_25_hours() {
+        let df = signals("2024-10-27T00:00:00+02:00").as_polars_df().unwrap();
+
+        assert_eq!(df.height(), 25);
+        // 02:00 happens first in summer time (00:00Z), then in winter time (01:00Z)
+        assert_consecutive_hours(&df, "2024-10-26T22:00:00Z", "2024-10-27T23:00:00Z");
+        let levels = levels(&df);
+        assert_eq!(&levels[1..5], ["GREEN", "ORANGE", "ORANGE", "GREEN"]);
+    }
+
+    #[test]
+    fn regular_day_has_24_hours() {
+        let df = signals("2024-10-28T00:00:00+01:00").as_polars_df().unwrap();
+
+        assert_eq!(df.height(), 24);
+        assert_consecutive_hours(&df, "2024-10-27T23:00:00Z", "2024-10-28T23:00:00Z");
+    }
+}
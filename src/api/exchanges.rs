@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::physical_flows::{Country, FlowDirection};
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct Exchanges<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for Exchanges<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ScheduledExchangesResponse {
+    pub scheduled_exchanges: Vec<BorderSeries>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NetTransferCapacitiesResponse {
+    pub net_transfer_capacities: Vec<BorderSeries>,
+}
+
+/// Values from a country to another, in MW
+#[derive(Deserialize, Debug)]
+pub struct BorderSeries {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "sender_country_eic_code")]
+    pub sender: Country,
+    #[serde(rename = "receiver_country_eic_code")]
+    pub receiver: Country,
+    pub values: Vec<BorderValue>,
+}
+
+impl BorderSeries {
+    /// The foreign country of the border
+    pub fn border(&self) -> &Country {
+        if self.sender == Country::France {
+            &self.receiver
+        } else {
+            &self.sender
+        }
+    }
+
+    pub fn direction(&self) -> FlowDirection {
+        if self.sender == Country::France {
+            FlowDirection::Export
+        } else {
+            FlowDirection::Import
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BorderValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub value: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+impl<'a> Exchanges<'a> {
+    const SCHEDULED_EXCHANGES_URL: &'static str =
+        "/open_api/scheduled_exchanges/v1/scheduled_exchanges";
+    const NTC_URL: &'static str = "/open_api/net_transfer_capacities/v1/net_transfer_capacities";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the commercial schedules on every border, in both directions
+    pub fn scheduled_exchanges(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<ScheduledExchangesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: ScheduledExchangesResponse =
+            get_response(self.client, Exchanges::SCHEDULED_EXCHANGES_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the net transfer capacities on every border, in both directions
+    pub fn net_transfer_capacities(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<NetTransferCapacitiesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: NetTransferCapacitiesResponse =
+            get_response(self.client, Exchanges::NTC_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the schedules and capacities of the range on a common time
+    /// index, see [`utilisation_df`]
+    pub fn utilisation(&self, date_range: DateRange) -> Result<DataFrame, anyhow::Error> {
+        let schedules = self.scheduled_exchanges(Some(date_range.clone()))?;
+        let capacities = self.net_transfer_capacities(Some(date_range))?;
+
+        utilisation_df(&schedules, &capacities)
+    }
+}
+
+fn unknown_countries(series: &[BorderSeries]) -> Vec<String> {
+    let mut unknown_values = vec![];
+    for s in series {
+        for country in [&s.sender, &s.receiver] {
+            if let Country::Unknown(eic_code) = country {
+                unknown_values.push(eic_code.clone());
+            }
+        }
+    }
+    unknown_values
+}
+
+impl UnknownValues for ScheduledExchangesResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        unknown_countries(&self.scheduled_exchanges)
+    }
+}
+
+impl UnknownValues for NetTransferCapacitiesResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        unknown_countries(&self.net_transfer_capacities)
+    }
+}
+
+/// Returns one row per border, direction and interval
+fn series_as_polars_df(series: &[BorderSeries]) -> Result<DataFrame, anyhow::Error> {
+    let mut borders: Vec<String> = vec![];
+    let mut directions: Vec<String> = vec![];
+    let mut start_dates: Vec<NaiveDateTime> = vec![];
+    let mut end_dates: Vec<NaiveDateTime> = vec![];
+    let mut values: Vec<f64> = vec![];
+    let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+    for s in series {
+        for v in &s.values {
+            borders.push(s.border().to_string());
+            directions.push(s.direction().to_string());
+            start_dates.push(v.start_date.naive_utc());
+            end_dates.push(v.end_date.naive_utc());
+            values.push(v.value);
+            updated_dates.push(v.updated_date.map(|ud| ud.naive_utc()));
+        }
+    }
+
+    let df = DataFrame::new(vec![
+        Series::new("border".into(), borders),
+        Series::new("direction".into(), directions),
+        Series::new("start_date".into(), start_dates),
+        Series::new("end_date".into(), end_dates),
+        Series::new("value".into(), values),
+        Series::new("updated_date".into(), updated_dates),
+    ])?;
+
+    Ok(df)
+}
+
+impl ScheduledExchangesResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        series_as_polars_df(&self.scheduled_exchanges)
+    }
+}
+
+impl NetTransferCapacitiesResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        series_as_polars_df(&self.net_transfer_capacities)
+    }
+}
+
+/// Puts schedules and capacities on a common border, direction and interval
+/// index, with a `utilisation` column (schedule / capacity). Intervals missing
+/// on one side, or with no capacity, get a null utilisation
+pub fn utilisation_df(
+    schedules: &ScheduledExchangesResponse,
+    capacities: &NetTransferCapacitiesResponse,
+) -> Result<DataFrame, anyhow::Error> {
+    type Key = (String, String, NaiveDateTime, NaiveDateTime);
+    let mut rows: BTreeMap<Key, (Option<f64>, Option<f64>)> = BTreeMap::new();
+
+    let key = |s: &BorderSeries, v: &BorderValue| {
+        (
+            s.border().to_string(),
+            s.direction().to_string(),
+            v.start_date.naive_utc(),
+            v.end_date.naive_utc(),
+        )
+    };
+    for s in &schedules.scheduled_exchanges {
+        for v in &s.values {
+            rows.entry(key(s, v)).or_default().0 = Some(v.value);
+        }
+    }
+    for s in &capacities.net_transfer_capacities {
+        for v in &s.values {
+            rows.entry(key(s, v)).or_default().1 = Some(v.value);
+        }
+    }
+
+    let mut borders: Vec<String> = vec![];
+    let mut directions: Vec<String> = vec![];
+    let mut start_dates: Vec<NaiveDateTime> = vec![];
+    let mut end_dates: Vec<NaiveDateTime> = vec![];
+    let mut schedule_values: Vec<Option<f64>> = vec![];
+    let mut capacity_values: Vec<Option<f64>> = vec![];
+    let mut utilisations: Vec<Option<f64>> = vec![];
+
+    for ((border, direction, start_date, end_date), (schedule, capacity)) in rows {
+        borders.push(border);
+        directions.push(direction);
+        start_dates.push(start_date);
+        end_dates.push(end_date);
+        schedule_values.push(schedule);
+        capacity_values.push(capacity);
+        utilisations.push(match (schedule, capacity) {
+            (Some(schedule), Some(capacity)) if capacity > 0.0 => Some(schedule / capacity),
+            _ => None,
+        });
+    }
+
+    let df = DataFrame::new(vec![
+        Series::new("border".into(), borders),
+        Series::new("direction".into(), directions),
+        Series::new("start_date".into(), start_dates),
+        Series::new("end_date".into(), end_dates),
+        Series::new("schedule".into(), schedule_values),
+        Series::new("capacity".into(), capacity_values),
+        Series::new("utilisation".into(), utilisations),
+    ])?;
+
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// French exports to Spain, one value per hour from midnight
+    fn border_series(values: &[f64]) -> String {
+        let values: Vec<String> = values
+            .iter()
+            .enumerate()
+            .map(|(h, value)| {
+                format!(
+                    r#"{{"start_date":"2024-01-15T{h:02}:00:00Z","end_date":"2024-01-15T{:02}:00:00Z","value":{value},"updated_date":null}}"#,
+                    h + 1
+                )
+            })
+            .collect();
+        format!(
+            r#"[{{"start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-16T00:00:00Z","sender_country_eic_code":"{}","receiver_country_eic_code":"{}","values":[{}]}}]"#,
+            Country::France.eic_code(),
+            Country::Spain.eic_code(),
+            values.join(",")
+        )
+    }
+
+    #[test]
+    fn utilisation_is_null_without_capacity() {
+        let schedules: ScheduledExchangesResponse = serde_json::from_str(&format!(
+            r#"{{"scheduled_exchanges":{}}}"#,
+            border_series(&[500.0, 300.0, 200.0])
+        ))
+        .unwrap();
+        // No capacity is published for the last hour
+        let capacities: NetTransferCapacitiesResponse = serde_json::from_str(&format!(
+            r#"{{"net_transfer_capacities":{}}}"#,
+            border_series(&[1000.0, 0.0])
+        ))
+        .unwrap();
+
+        let df = utilisation_df(&schedules, &capacities).unwrap();
+        let column = |name: &str| -> Vec<Option<f64>> {
+            df.column(name)
+                .unwrap()
+                .f64()
+                .unwrap()
+                .into_iter()
+                .collect()
+        };
+        assert_eq!(column("capacity"), vec![Some(1000.0), Some(0.0), None]);
+        assert_eq!(column("utilisation"), vec![Some(0.5), None, None]);
+    }
+}
@@ -1,19 +1,28 @@
 use core::fmt;
+use std::collections::BTreeMap;
 
 use chrono::{DateTime, NaiveDateTime, Utc};
 use polars::prelude::*;
 use polars::{frame::DataFrame, series::Series};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::ApiClient;
 
-use super::DateRange;
+use super::actual_generation::ActualProductionType;
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
 
 pub struct GenerationForecast<'a> {
     client: &'a dyn ApiClient,
+    strict: bool,
 }
 
-#[derive(Debug)]
+impl StrictMode for GenerationForecast<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub enum ProductionType {
     /// Agrégée France
     AggregatedFrance,
@@ -43,8 +52,8 @@ impl fmt::Display for ProductionType {
     }
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
 pub enum ProductionTypeResponse {
     /// production des moyens programmables agrégée sur la France
     AggregatedProgrammableFrance,
@@ -56,12 +65,31 @@ pub enum ProductionTypeResponse {
     Solar,
     AggregatedCpc,
     /// Installations bénéficiant d'un contrat d'achat indexé aux prix de marché Trading Region France
-    #[serde(rename = "MDSETRF")]
     MdseTrf,
     /// Installations bénéficiant d'un contrat d'achat indexé sur le tarif réglementé de fourniture de gaz STS
-    #[serde(rename = "MDSESTS")]
     MdseSts,
+    /// A production type not (yet) known by this crate
+    Unknown(String),
+}
+
+impl ProductionTypeResponse {
+    /// The matching production type of the actual generation and installed
+    /// capacities APIs, `None` for aggregates and contract based types
+    pub fn actual_production_type(&self) -> Option<ActualProductionType> {
+        match self {
+            ProductionTypeResponse::WindOnshore => Some(ActualProductionType::WindOnshore),
+            ProductionTypeResponse::WindOffshore => Some(ActualProductionType::WindOffshore),
+            ProductionTypeResponse::Solar => Some(ActualProductionType::Solar),
+            ProductionTypeResponse::AggregatedProgrammableFrance
+            | ProductionTypeResponse::AggregatedNonProgrammableFrance
+            | ProductionTypeResponse::AggregatedCpc
+            | ProductionTypeResponse::MdseTrf
+            | ProductionTypeResponse::MdseSts
+            | ProductionTypeResponse::Unknown(_) => None,
+        }
+    }
 }
+
 impl fmt::Display for ProductionTypeResponse {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let pt = match self {
@@ -77,18 +105,44 @@ impl fmt::Display for ProductionTypeResponse {
             ProductionTypeResponse::AggregatedCpc => "AGGREGATED_CPC",
             ProductionTypeResponse::MdseTrf => "MDSE_TRF",
             ProductionTypeResponse::MdseSts => "MDSE_STS",
+            ProductionTypeResponse::Unknown(pt) => pt,
         };
         write!(f, "{}", pt)
     }
 }
 
-#[derive(Debug, Deserialize)]
+impl From<String> for ProductionTypeResponse {
+    fn from(pt: String) -> Self {
+        match pt.as_str() {
+            "AGGREGATED_PROGRAMMABLE_FRANCE" => {
+                ProductionTypeResponse::AggregatedProgrammableFrance
+            }
+            "AGGREGATED_NON_PROGRAMMABLE_FRANCE" => {
+                ProductionTypeResponse::AggregatedNonProgrammableFrance
+            }
+            "WIND_ONSHORE" => ProductionTypeResponse::WindOnshore,
+            "WIND_OFFSHORE" => ProductionTypeResponse::WindOffshore,
+            "SOLAR" => ProductionTypeResponse::Solar,
+            "AGGREGATED_CPC" => ProductionTypeResponse::AggregatedCpc,
+            // RTE sends them without underscore
+            "MDSETRF" | "MDSE_TRF" => ProductionTypeResponse::MdseTrf,
+            "MDSESTS" | "MDSE_STS" => ProductionTypeResponse::MdseSts,
+            _ => ProductionTypeResponse::Unknown(pt),
+        }
+    }
+}
+
+/// The type (horizon) of a forecast, used both in queries and in responses
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
 pub enum ForecastType {
     Current,
     Intraday,
     Tomorrow,
     AfterTomorrow,
     AfterAfterTomorrow, // Lol, this is a dumb name
+    /// A forecast type not (yet) known by this crate
+    Unknown(String),
 }
 
 impl fmt::Display for ForecastType {
@@ -99,11 +153,70 @@ impl fmt::Display for ForecastType {
             ForecastType::Tomorrow => "D-1",
             ForecastType::AfterTomorrow => "D-2",
             ForecastType::AfterAfterTomorrow => "D-3",
+            ForecastType::Unknown(ft) => ft,
         };
         write!(f, "{}", ft)
     }
 }
 
+impl From<String> for ForecastType {
+    fn from(ft: String) -> Self {
+        match ft.as_str() {
+            "CURRENT" => ForecastType::Current,
+            "ID" => ForecastType::Intraday,
+            "D-1" => ForecastType::Tomorrow,
+            "D-2" => ForecastType::AfterTomorrow,
+            "D-3" => ForecastType::AfterAfterTomorrow,
+            _ => ForecastType::Unknown(ft),
+        }
+    }
+}
+
+impl From<ForecastType> for String {
+    fn from(ft: ForecastType) -> Self {
+        ft.to_string()
+    }
+}
+
+/// The sub type of a forecast, telling how it was produced
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum ForecastSubType {
+    /// Computed automatically by RTE's models
+    Automatic,
+    /// Adjusted by RTE's operators
+    Manual,
+    /// A forecast sub type not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for ForecastSubType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let st = match self {
+            ForecastSubType::Automatic => "AUTOMATIC",
+            ForecastSubType::Manual => "MANUAL",
+            ForecastSubType::Unknown(st) => st,
+        };
+        write!(f, "{}", st)
+    }
+}
+
+impl From<String> for ForecastSubType {
+    fn from(st: String) -> Self {
+        match st.as_str() {
+            "AUTOMATIC" => ForecastSubType::Automatic,
+            "MANUAL" => ForecastSubType::Manual,
+            _ => ForecastSubType::Unknown(st),
+        }
+    }
+}
+
+impl From<ForecastSubType> for String {
+    fn from(st: ForecastSubType) -> Self {
+        st.to_string()
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ForecastResponse {
     pub forecasts: Vec<Forecast>,
@@ -112,10 +225,10 @@ pub struct ForecastResponse {
 #[derive(Deserialize, Debug)]
 pub struct Forecast {
     #[serde(rename = "type")]
-    pub ty: String, // XXX enum
+    pub ty: ForecastType,
 
-    pub sub_type: Option<String>,                // XXX enum
-    pub production_type: ProductionTypeResponse, // XXX enum
+    pub sub_type: Option<ForecastSubType>,
+    pub production_type: ProductionTypeResponse,
 
     pub start_date: DateTime<Utc>,
     pub end_date: DateTime<Utc>,
@@ -135,7 +248,10 @@ impl<'a> GenerationForecast<'a> {
     const URL: &'static str = "/open_api/generation_forecast/v2/forecasts";
 
     pub fn new(client: &'a dyn ApiClient) -> Self {
-        Self { client }
+        Self {
+            client,
+            strict: false,
+        }
     }
 
     /// Returns a short term forecast given the forecast type
@@ -160,22 +276,209 @@ impl<'a> GenerationForecast<'a> {
             qs.append(&mut date_range.to_query_string());
         }
 
-        let response = self.client.http_get(GenerationForecast::URL, &qs);
+        let res: ForecastResponse = get_response(self.client, GenerationForecast::URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns how the forecast of each target interval evolved across the
+    /// horizons, from D-3 to CURRENT, with one column per horizon.
+    /// The horizons RTE fails to return are left out
+    pub fn horizons(
+        &self,
+        production_type: ProductionType,
+        date_range: DateRange,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut forecasts = vec![];
+        let mut error = None;
+        for forecast_type in [
+            ForecastType::AfterAfterTomorrow,
+            ForecastType::AfterTomorrow,
+            ForecastType::Tomorrow,
+            ForecastType::Intraday,
+            ForecastType::Current,
+        ] {
+            match self.short_term(
+                Some(production_type),
+                Some(forecast_type),
+                Some(date_range.clone()),
+            ) {
+                Ok(mut response) => forecasts.append(&mut response.forecasts),
+                Err(e) => error = Some(e),
+            }
+        }
+
+        // Only fails when no horizon at all could be fetched
+        match error {
+            Some(e) if forecasts.is_empty() => Err(e),
+            _ => ForecastResponse { forecasts }.pivot_by_type(),
+        }
+    }
+}
+
+impl UnknownValues for ForecastResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for forecast in &self.forecasts {
+            if let ForecastType::Unknown(ft) = &forecast.ty {
+                unknown_values.push(ft.clone());
+            }
+            if let Some(ForecastSubType::Unknown(st)) = &forecast.sub_type {
+                unknown_values.push(st.clone());
+            }
+            if let ProductionTypeResponse::Unknown(pt) = &forecast.production_type {
+                unknown_values.push(pt.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl ForecastResponse {
+    /// Returns all the forecasts of the response in a long format frame, with
+    /// the `production_type`, `type` and `sub_type` of each row
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut production_types: Vec<String> = vec![];
+        let mut types: Vec<String> = vec![];
+        let mut sub_types: Vec<Option<String>> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut updated_dates: Vec<NaiveDateTime> = vec![];
+        let mut values: Vec<f64> = vec![];
+        let mut load_factors: Vec<Option<f64>> = vec![];
+
+        for forecast in &self.forecasts {
+            for fv in &forecast.values {
+                production_types.push(forecast.production_type.to_string());
+                types.push(forecast.ty.to_string());
+                sub_types.push(forecast.sub_type.as_ref().map(|st| st.to_string()));
+                start_dates.push(fv.start_date.naive_utc());
+                end_dates.push(fv.end_date.naive_utc());
+                updated_dates.push(fv.updated_date.naive_utc());
+                values.push(fv.value);
+                load_factors.push(fv.load_factor);
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("production_type".into(), production_types),
+            Series::new("type".into(), types),
+            Series::new("sub_type".into(), sub_types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("updated_date".into(), updated_dates),
+            Series::new("value".into(), values),
+            Series::new("load_factor".into(), load_factors),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Returns a wide frame with one value column per production type, indexed
+    /// by `type`, `start_date` and `end_date`, so that the forecast types are
+    /// never mixed.
+    /// When several forecasts of the same type cover the same interval for a
+    /// production type (e.g. with different sub types), the most recently
+    /// updated value is kept
+    pub fn pivot_by_production_type(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut production_types: Vec<&ProductionTypeResponse> = vec![];
+        for forecast in &self.forecasts {
+            if !production_types.contains(&&forecast.production_type) {
+                production_types.push(&forecast.production_type);
+            }
+        }
+
+        type Key = (String, NaiveDateTime, NaiveDateTime);
+        let mut rows: BTreeMap<Key, Vec<Option<&ForecastValue>>> = BTreeMap::new();
+        for forecast in &self.forecasts {
+            let i = production_types
+                .iter()
+                .position(|pt| **pt == forecast.production_type)
+                .unwrap();
+            for fv in &forecast.values {
+                let key = (
+                    forecast.ty.to_string(),
+                    fv.start_date.naive_utc(),
+                    fv.end_date.naive_utc(),
+                );
+                let row = rows
+                    .entry(key)
+                    .or_insert_with(|| vec![None; production_types.len()]);
+                match row[i] {
+                    Some(current) if current.updated_date > fv.updated_date => {}
+                    _ => row[i] = Some(fv),
+                }
+            }
+        }
 
-        let reply = response.unwrap();
+        let types: Vec<&str> = rows.keys().map(|(ty, _, _)| ty.as_str()).collect();
+        let start_dates: Vec<NaiveDateTime> = rows.keys().map(|(_, start, _)| *start).collect();
+        let end_dates: Vec<NaiveDateTime> = rows.keys().map(|(_, _, end)| *end).collect();
 
-        let res = serde_json::from_str(&reply);
-        if let Err(e) = res {
-            eprintln!(
-                "Error: parsing reply of {}?{:?} => '{:?}': {:?}",
-                GenerationForecast::URL,
-                qs,
-                reply,
-                e
-            );
-            return Err(anyhow::Error::msg("Failed to parse response"));
+        let mut columns = vec![
+            Series::new("type".into(), types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+        ];
+        for (i, pt) in production_types.iter().enumerate() {
+            let values: Vec<Option<f64>> = rows.values().map(|v| v[i].map(|fv| fv.value)).collect();
+            columns.push(Series::new(pt.to_string().into(), values));
         }
-        Ok(res.unwrap())
+
+        let df = DataFrame::new(columns)?;
+
+        Ok(df)
+    }
+
+    /// Returns a wide frame with one value column per forecast type, indexed
+    /// by `production_type`, `start_date` and `end_date`.
+    /// When several forecasts of the same type cover the same interval (e.g.
+    /// with different sub types), the most recently updated value is kept
+    pub fn pivot_by_type(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut types: Vec<&ForecastType> = vec![];
+        for forecast in &self.forecasts {
+            if !types.contains(&&forecast.ty) {
+                types.push(&forecast.ty);
+            }
+        }
+
+        type Key = (String, NaiveDateTime, NaiveDateTime);
+        let mut rows: BTreeMap<Key, Vec<Option<&ForecastValue>>> = BTreeMap::new();
+        for forecast in &self.forecasts {
+            let i = types.iter().position(|ty| **ty == forecast.ty).unwrap();
+            for fv in &forecast.values {
+                let key = (
+                    forecast.production_type.to_string(),
+                    fv.start_date.naive_utc(),
+                    fv.end_date.naive_utc(),
+                );
+                let row = rows.entry(key).or_insert_with(|| vec![None; types.len()]);
+                match row[i] {
+                    Some(current) if current.updated_date > fv.updated_date => {}
+                    _ => row[i] = Some(fv),
+                }
+            }
+        }
+
+        let production_types: Vec<&str> = rows.keys().map(|(pt, _, _)| pt.as_str()).collect();
+        let start_dates: Vec<NaiveDateTime> = rows.keys().map(|(_, start, _)| *start).collect();
+        let end_dates: Vec<NaiveDateTime> = rows.keys().map(|(_, _, end)| *end).collect();
+
+        let mut columns = vec![
+            Series::new("production_type".into(), production_types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+        ];
+        for (i, ty) in types.iter().enumerate() {
+            let values: Vec<Option<f64>> = rows.values().map(|v| v[i].map(|fv| fv.value)).collect();
+            columns.push(Series::new(ty.to_string().into(), values));
+        }
+
+        let df = DataFrame::new(columns)?;
+
+        Ok(df)
     }
 }
 
@@ -0,0 +1,269 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::actual_generation::ActualProductionType;
+use super::generation::ForecastResponse;
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct InstalledCapacities<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for InstalledCapacities<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CapacitiesPerProductionTypeResponse {
+    pub capacities_per_production_type: Vec<CapacitiesPerProductionType>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CapacitiesPerProductionType {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ProductionTypeCapacity>,
+}
+
+/// Installed capacity of a production type, in MW, over its validity period
+#[derive(Deserialize, Debug)]
+pub struct ProductionTypeCapacity {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub production_type: ActualProductionType,
+    pub value: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CapacitiesPerProductionUnitResponse {
+    pub capacities_per_production_unit: Vec<CapacitiesPerProductionUnit>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CapacitiesPerProductionUnit {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<ProductionUnitCapacity>,
+}
+
+/// Installed capacity of a production unit, in MW, over its validity period
+#[derive(Deserialize, Debug)]
+pub struct ProductionUnitCapacity {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub production_type: ActualProductionType,
+    /// EIC code of the unit
+    pub code_eic: String,
+    pub name: String,
+    pub installed_capacity: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+impl<'a> InstalledCapacities<'a> {
+    const PER_PRODUCTION_TYPE_URL: &'static str =
+        "/open_api/generation_installed_capacities/v1/capacities_per_production_type";
+    const PER_PRODUCTION_UNIT_URL: &'static str =
+        "/open_api/generation_installed_capacities/v1/capacities_per_production_unit";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the installed capacities per production type
+    pub fn per_production_type(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<CapacitiesPerProductionTypeResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: CapacitiesPerProductionTypeResponse = get_response(
+            self.client,
+            InstalledCapacities::PER_PRODUCTION_TYPE_URL,
+            &qs,
+        )?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the installed capacities of each production unit above 100MW
+    pub fn per_production_unit(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<CapacitiesPerProductionUnitResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: CapacitiesPerProductionUnitResponse = get_response(
+            self.client,
+            InstalledCapacities::PER_PRODUCTION_UNIT_URL,
+            &qs,
+        )?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for CapacitiesPerProductionTypeResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for capacities in &self.capacities_per_production_type {
+            for capacity in &capacities.values {
+                if let ActualProductionType::Unknown(pt) = &capacity.production_type {
+                    unknown_values.push(pt.clone());
+                }
+            }
+        }
+        unknown_values
+    }
+}
+
+impl UnknownValues for CapacitiesPerProductionUnitResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for capacities in &self.capacities_per_production_unit {
+            for capacity in &capacities.values {
+                if let ActualProductionType::Unknown(pt) = &capacity.production_type {
+                    unknown_values.push(pt.clone());
+                }
+            }
+        }
+        unknown_values
+    }
+}
+
+impl CapacitiesPerProductionTypeResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut production_types: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+        let mut values: Vec<f64> = vec![];
+
+        for capacities in &self.capacities_per_production_type {
+            for capacity in &capacities.values {
+                production_types.push(capacity.production_type.to_string());
+                start_dates.push(capacity.start_date.naive_utc());
+                end_dates.push(capacity.end_date.naive_utc());
+                updated_dates.push(capacity.updated_date.map(|ud| ud.naive_utc()));
+                values.push(capacity.value);
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("production_type".into(), production_types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("updated_date".into(), updated_dates),
+            Series::new("installed_capacity".into(), values),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Returns the installed capacity of a production type at a given time
+    pub fn capacity_at(
+        &self,
+        production_type: &ActualProductionType,
+        at: DateTime<Utc>,
+    ) -> Option<f64> {
+        self.capacities_per_production_type
+            .iter()
+            .flat_map(|capacities| &capacities.values)
+            .find(|capacity| {
+                capacity.production_type == *production_type
+                    && capacity.start_date <= at
+                    && at < capacity.end_date
+            })
+            .map(|capacity| capacity.value)
+    }
+
+    /// Attaches the installed capacity valid at each forecast interval to the
+    /// `ForecastResponse::as_polars_df` frame, along with the resulting
+    /// `capacity_factor`. Production types without a known capacity (e.g.
+    /// aggregated ones) are left null
+    pub fn join_forecast(
+        &self,
+        forecast: &ForecastResponse,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut installed_capacities: Vec<Option<f64>> = vec![];
+        let mut capacity_factors: Vec<Option<f64>> = vec![];
+
+        // Same row order as ForecastResponse::as_polars_df
+        for f in &forecast.forecasts {
+            let production_type = f.production_type.actual_production_type();
+            for fv in &f.values {
+                let capacity = production_type
+                    .as_ref()
+                    .and_then(|pt| self.capacity_at(pt, fv.start_date));
+                installed_capacities.push(capacity);
+                capacity_factors.push(capacity.map(|c| fv.value / c));
+            }
+        }
+
+        let mut df = forecast.as_polars_df()?;
+        df.with_column(Series::new(
+            "installed_capacity".into(),
+            installed_capacities,
+        ))?;
+        df.with_column(Series::new("capacity_factor".into(), capacity_factors))?;
+
+        Ok(df)
+    }
+}
+
+impl CapacitiesPerProductionUnitResponse {
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut eic_codes: Vec<&str> = vec![];
+        let mut names: Vec<&str> = vec![];
+        let mut production_types: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+        let mut values: Vec<f64> = vec![];
+
+        for capacities in &self.capacities_per_production_unit {
+            for capacity in &capacities.values {
+                eic_codes.push(&capacity.code_eic);
+                names.push(&capacity.name);
+                production_types.push(capacity.production_type.to_string());
+                start_dates.push(capacity.start_date.naive_utc());
+                end_dates.push(capacity.end_date.naive_utc());
+                updated_dates.push(capacity.updated_date.map(|ud| ud.naive_utc()));
+                values.push(capacity.installed_capacity);
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("eic_code".into(), eic_codes),
+            Series::new("name".into(), names),
+            Series::new("production_type".into(), production_types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("updated_date".into(), updated_dates),
+            Series::new("installed_capacity".into(), values),
+        ])?;
+
+        Ok(df)
+    }
+}
@@ -1,12 +1,103 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::de::DeserializeOwned;
 
+use crate::ApiClient;
+
+pub mod actual_generation;
+pub mod balancing_capacity;
+pub mod balancing_energy;
+pub mod capacity_mechanism;
+pub mod co2;
 pub mod consumption;
+pub mod demand_response;
+pub mod ecowatt;
+pub mod exchanges;
 pub mod generation;
+pub mod installed_capacities;
+pub mod network_unavailability;
+pub mod physical_flows;
+pub mod regional;
+pub mod tempo;
+pub mod unavailability;
+pub mod wholesale_market;
 
 pub trait FormatToApiFmt {
     fn to_api_format(&self) -> String;
 }
 
+/// Response enums keep the values they don't know about in an `Unknown`
+/// variant, so that new RTE values don't break parsing
+pub trait UnknownValues {
+    /// Returns the raw values of the response that are unknown to this crate
+    fn unknown_values(&self) -> Vec<String>;
+}
+
+/// Clients whose responses may contain values unknown to this crate
+pub trait StrictMode: Sized {
+    /// Gives access to the strict mode flag of the client
+    fn strict_mode(&mut self) -> &mut bool;
+
+    /// In strict mode, responses containing values unknown to this crate are
+    /// turned into errors instead of being kept as `Unknown`
+    fn with_strict_mode(mut self, strict: bool) -> Self {
+        *self.strict_mode() = strict;
+        self
+    }
+}
+
+/// Fails if the response contains unknown values, used by the strict mode
+pub(crate) fn ensure_known_values(response: &impl UnknownValues) -> anyhow::Result<()> {
+    let unknown_values = response.unknown_values();
+    if !unknown_values.is_empty() {
+        return Err(anyhow::Error::msg(format!(
+            "Response contains unknown values: {}",
+            unknown_values.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Queries the given endpoint and parses its JSON reply
+pub(crate) fn get_response<T: DeserializeOwned>(
+    client: &dyn ApiClient,
+    url: &str,
+    qs: &[(String, String)],
+) -> Result<T, anyhow::Error> {
+    let reply = client.http_get(url, qs)?;
+
+    match serde_json::from_str(&reply) {
+        Ok(res) => Ok(res),
+        Err(e) => {
+            eprintln!(
+                "Error: parsing reply of {}?{:?} => '{:?}': {:?}",
+                url, qs, reply, e
+            );
+            Err(anyhow::Error::msg("Failed to parse response"))
+        }
+    }
+}
+
+/// Returns the distinct `start_date`/`end_date` intervals of a frame, to
+/// compute values joined back on them
+pub(crate) fn intervals(
+    df: &DataFrame,
+) -> Result<Vec<(NaiveDateTime, NaiveDateTime)>, anyhow::Error> {
+    let intervals = df.select(["start_date", "end_date"])?.unique_stable(
+        None,
+        UniqueKeepStrategy::First,
+        None,
+    )?;
+    let start_dates = intervals.column("start_date")?.datetime()?;
+    let end_dates = intervals.column("end_date")?.datetime()?;
+
+    Ok(start_dates
+        .as_datetime_iter()
+        .zip(end_dates.as_datetime_iter())
+        .filter_map(|(start_date, end_date)| start_date.zip(end_date))
+        .collect())
+}
+
 impl FormatToApiFmt for DateTime<Utc> {
     fn to_api_format(&self) -> String {
         // Define the desired format for your API
@@ -15,7 +106,7 @@ impl FormatToApiFmt for DateTime<Utc> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DateRange {
     pub start: DateTime<Utc>,
     pub end: DateTime<Utc>,
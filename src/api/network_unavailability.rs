@@ -0,0 +1,250 @@
+use core::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::unavailability::{UnavailabilityStatus, UnavailabilityType};
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct NetworkUnavailability<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for NetworkUnavailability<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum AssetType {
+    /// Ligne
+    Line,
+    /// Transformateur
+    Transformer,
+    /// Interconnexion
+    Interconnector,
+    /// An asset type not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for AssetType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = match self {
+            AssetType::Line => "LINE",
+            AssetType::Transformer => "TRANSFORMER",
+            AssetType::Interconnector => "INTERCONNECTOR",
+            AssetType::Unknown(at) => at,
+        };
+        write!(f, "{}", at)
+    }
+}
+
+impl From<String> for AssetType {
+    fn from(at: String) -> Self {
+        match at.as_str() {
+            "LINE" => AssetType::Line,
+            "TRANSFORMER" => AssetType::Transformer,
+            "INTERCONNECTOR" => AssetType::Interconnector,
+            _ => AssetType::Unknown(at),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum VoltageLevel {
+    Kv400,
+    Kv225,
+    Kv150,
+    Kv90,
+    Kv63,
+    /// A voltage level not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for VoltageLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let vl = match self {
+            VoltageLevel::Kv400 => "400kV",
+            VoltageLevel::Kv225 => "225kV",
+            VoltageLevel::Kv150 => "150kV",
+            VoltageLevel::Kv90 => "90kV",
+            VoltageLevel::Kv63 => "63kV",
+            VoltageLevel::Unknown(vl) => vl,
+        };
+        write!(f, "{}", vl)
+    }
+}
+
+impl From<String> for VoltageLevel {
+    fn from(vl: String) -> Self {
+        // RTE isn't consistent on the unit suffix
+        let kv = vl
+            .trim()
+            .trim_end_matches("kV")
+            .trim_end_matches("KV")
+            .trim();
+        match kv {
+            "400" => VoltageLevel::Kv400,
+            "225" => VoltageLevel::Kv225,
+            "150" => VoltageLevel::Kv150,
+            "90" => VoltageLevel::Kv90,
+            "63" => VoltageLevel::Kv63,
+            _ => VoltageLevel::Unknown(vl),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NetworkUnavailabilitiesResponse {
+    pub transmission_network_unavailabilities: Vec<NetworkUnavailabilityEvent>,
+}
+
+/// A version of an outage event of a transmission asset
+#[derive(Deserialize, Debug)]
+pub struct NetworkUnavailabilityEvent {
+    /// Identifier of the event, shared by all its versions
+    pub identifier: String,
+    pub version: u32,
+    pub creation_date: DateTime<Utc>,
+    pub updated_date: DateTime<Utc>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub ty: UnavailabilityType,
+    pub status: UnavailabilityStatus,
+    pub asset: Asset,
+    pub values: Vec<NetworkUnavailabilityValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Asset {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub asset_type: AssetType,
+    pub voltage_level: VoltageLevel,
+}
+
+/// A period during which the asset is out of service
+#[derive(Deserialize, Debug)]
+pub struct NetworkUnavailabilityValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+}
+
+impl<'a> NetworkUnavailability<'a> {
+    const URL: &'static str =
+        "/open_api/unavailability_additional_information/v6/transmission_network_unavailabilities";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the transmission asset outages over the range, optionally only
+    /// for an asset type and/or voltage level
+    pub fn transmission_network(
+        &self,
+        asset_type: Option<AssetType>,
+        voltage_level: Option<VoltageLevel>,
+        date_range: Option<DateRange>,
+    ) -> Result<NetworkUnavailabilitiesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let mut res: NetworkUnavailabilitiesResponse =
+            get_response(self.client, NetworkUnavailability::URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+
+        // The API has no filter on these, so it's done here
+        res.transmission_network_unavailabilities.retain(|event| {
+            asset_type
+                .as_ref()
+                .is_none_or(|at| event.asset.asset_type == *at)
+                && voltage_level
+                    .as_ref()
+                    .is_none_or(|vl| event.asset.voltage_level == *vl)
+        });
+
+        Ok(res)
+    }
+}
+
+impl UnknownValues for NetworkUnavailabilitiesResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for event in &self.transmission_network_unavailabilities {
+            if let UnavailabilityType::Unknown(ut) = &event.ty {
+                unknown_values.push(ut.clone());
+            }
+            if let UnavailabilityStatus::Unknown(us) = &event.status {
+                unknown_values.push(us.clone());
+            }
+            if let AssetType::Unknown(at) = &event.asset.asset_type {
+                unknown_values.push(at.clone());
+            }
+            if let VoltageLevel::Unknown(vl) = &event.asset.voltage_level {
+                unknown_values.push(vl.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl NetworkUnavailabilitiesResponse {
+    /// Returns one row per outage interval of each event version
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut identifiers: Vec<&str> = vec![];
+        let mut versions: Vec<u32> = vec![];
+        let mut types: Vec<String> = vec![];
+        let mut statuses: Vec<String> = vec![];
+        let mut asset_names: Vec<&str> = vec![];
+        let mut asset_types: Vec<String> = vec![];
+        let mut voltage_levels: Vec<String> = vec![];
+        let mut updated_dates: Vec<NaiveDateTime> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+
+        for event in &self.transmission_network_unavailabilities {
+            for v in &event.values {
+                identifiers.push(&event.identifier);
+                versions.push(event.version);
+                types.push(event.ty.to_string());
+                statuses.push(event.status.to_string());
+                asset_names.push(&event.asset.name);
+                asset_types.push(event.asset.asset_type.to_string());
+                voltage_levels.push(event.asset.voltage_level.to_string());
+                updated_dates.push(event.updated_date.naive_utc());
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("identifier".into(), identifiers),
+            Series::new("version".into(), versions),
+            Series::new("type".into(), types),
+            Series::new("status".into(), statuses),
+            Series::new("asset_name".into(), asset_names),
+            Series::new("asset_type".into(), asset_types),
+            Series::new("voltage_level".into(), voltage_levels),
+            Series::new("updated_date".into(), updated_dates),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
@@ -0,0 +1,304 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct PhysicalFlows<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for PhysicalFlows<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// A country of the french interconnections, identified by its EIC code
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(from = "String")]
+pub enum Country {
+    France,
+    Belgium,
+    Germany,
+    Switzerland,
+    Italy,
+    Spain,
+    GreatBritain,
+    /// A country not (yet) known by this crate, with its EIC code
+    Unknown(String),
+}
+
+impl Country {
+    pub fn eic_code(&self) -> &str {
+        match self {
+            Country::France => "10YFR-RTE------C",
+            Country::Belgium => "10YBE----------2",
+            Country::Germany => "10Y1001A1001A83F",
+            Country::Switzerland => "10YCH-SWISSGRIDZ",
+            Country::Italy => "10YIT-GRTN-----B",
+            Country::Spain => "10YES-REE------0",
+            Country::GreatBritain => "10YGB----------A",
+            Country::Unknown(eic_code) => eic_code,
+        }
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Country::France => "FRANCE",
+            Country::Belgium => "BELGIUM",
+            Country::Germany => "GERMANY",
+            Country::Switzerland => "SWITZERLAND",
+            Country::Italy => "ITALY",
+            Country::Spain => "SPAIN",
+            Country::GreatBritain => "GREAT_BRITAIN",
+            Country::Unknown(eic_code) => eic_code,
+        };
+        write!(f, "{}", c)
+    }
+}
+
+impl From<String> for Country {
+    fn from(eic_code: String) -> Self {
+        match eic_code.as_str() {
+            "10YFR-RTE------C" => Country::France,
+            "10YBE----------2" => Country::Belgium,
+            // Germany alone, or the Germany-Luxembourg bidding zone
+            "10Y1001A1001A83F" | "10Y1001A1001A82H" => Country::Germany,
+            "10YCH-SWISSGRIDZ" => Country::Switzerland,
+            "10YIT-GRTN-----B" => Country::Italy,
+            "10YES-REE------0" => Country::Spain,
+            "10YGB----------A" => Country::GreatBritain,
+            _ => Country::Unknown(eic_code),
+        }
+    }
+}
+
+/// Direction of a flow, seen from France
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlowDirection {
+    Export,
+    Import,
+}
+
+impl fmt::Display for FlowDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let d = match self {
+            FlowDirection::Export => "EXPORT",
+            FlowDirection::Import => "IMPORT",
+        };
+        write!(f, "{}", d)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PhysicalFlowsResponse {
+    pub physical_flows: Vec<PhysicalFlow>,
+}
+
+/// Measured flows from a country to another, in MW
+#[derive(Deserialize, Debug)]
+pub struct PhysicalFlow {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "sender_country_eic_code")]
+    pub sender: Country,
+    #[serde(rename = "receiver_country_eic_code")]
+    pub receiver: Country,
+    pub values: Vec<PhysicalFlowValue>,
+}
+
+impl PhysicalFlow {
+    /// The foreign country of the border
+    pub fn border(&self) -> &Country {
+        if self.sender == Country::France {
+            &self.receiver
+        } else {
+            &self.sender
+        }
+    }
+
+    pub fn direction(&self) -> FlowDirection {
+        if self.sender == Country::France {
+            FlowDirection::Export
+        } else {
+            FlowDirection::Import
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PhysicalFlowValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub value: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+impl<'a> PhysicalFlows<'a> {
+    const URL: &'static str = "/open_api/physical_flow/v1/physical_flows";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the measured flows on every border, in both directions
+    pub fn physical_flows(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<PhysicalFlowsResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: PhysicalFlowsResponse = get_response(self.client, PhysicalFlows::URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for PhysicalFlowsResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for flow in &self.physical_flows {
+            for country in [&flow.sender, &flow.receiver] {
+                if let Country::Unknown(eic_code) = country {
+                    unknown_values.push(eic_code.clone());
+                }
+            }
+        }
+        unknown_values
+    }
+}
+
+impl PhysicalFlowsResponse {
+    /// Returns the flows of a single border
+    pub fn border(&self, country: &Country) -> Vec<&PhysicalFlow> {
+        self.physical_flows
+            .iter()
+            .filter(|flow| flow.border() == country)
+            .collect()
+    }
+
+    /// Returns one row per border, direction and interval
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut borders: Vec<String> = vec![];
+        let mut directions: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut values: Vec<f64> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for flow in &self.physical_flows {
+            for v in &flow.values {
+                borders.push(flow.border().to_string());
+                directions.push(flow.direction().to_string());
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                values.push(v.value);
+                updated_dates.push(v.updated_date.map(|ud| ud.naive_utc()));
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("border".into(), borders),
+            Series::new("direction".into(), directions),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("value".into(), values),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Returns the net flow (exports minus imports) of each border, one column
+    /// per border plus a `TOTAL` column, on a shared time index
+    pub fn net_flows_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut borders: Vec<&Country> = vec![];
+        for flow in &self.physical_flows {
+            if !borders.contains(&flow.border()) {
+                borders.push(flow.border());
+            }
+        }
+        borders.sort();
+
+        let mut rows: BTreeMap<(NaiveDateTime, NaiveDateTime), Vec<Option<f64>>> = BTreeMap::new();
+        for flow in &self.physical_flows {
+            let i = borders.iter().position(|b| *b == flow.border()).unwrap();
+            let sign = match flow.direction() {
+                FlowDirection::Export => 1.0,
+                FlowDirection::Import => -1.0,
+            };
+            for v in &flow.values {
+                let key = (v.start_date.naive_utc(), v.end_date.naive_utc());
+                let row = rows.entry(key).or_insert_with(|| vec![None; borders.len()]);
+                row[i] = Some(row[i].unwrap_or(0.0) + sign * v.value);
+            }
+        }
+
+        let start_dates: Vec<NaiveDateTime> = rows.keys().map(|(start, _)| *start).collect();
+        let end_dates: Vec<NaiveDateTime> = rows.keys().map(|(_, end)| *end).collect();
+        let totals: Vec<f64> = rows.values().map(|v| v.iter().flatten().sum()).collect();
+
+        let mut columns = vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+        ];
+        for (i, border) in borders.iter().enumerate() {
+            let values: Vec<Option<f64>> = rows.values().map(|v| v[i]).collect();
+            columns.push(Series::new(border.to_string().into(), values));
+        }
+        columns.push(Series::new("TOTAL".into(), totals));
+
+        let df = DataFrame::new(columns)?;
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(sender: &Country, receiver: &Country, value: f64) -> String {
+        format!(
+            r#"{{"start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","sender_country_eic_code":"{}","receiver_country_eic_code":"{}","values":[
+            {{"start_date":"2024-01-15T00:00:00Z","end_date":"2024-01-15T01:00:00Z","value":{value},"updated_date":null}}]}}"#,
+            sender.eic_code(),
+            receiver.eic_code()
+        )
+    }
+
+    #[test]
+    fn net_flows_are_exports_minus_imports() {
+        let reply = format!(
+            r#"{{"physical_flows":[{},{},{}]}}"#,
+            flow(&Country::France, &Country::Spain, 1000.0),
+            flow(&Country::Spain, &Country::France, 300.0),
+            flow(&Country::Belgium, &Country::France, 500.0)
+        );
+        let response: PhysicalFlowsResponse = serde_json::from_str(&reply).unwrap();
+
+        let df = response.net_flows_df().unwrap();
+        assert_eq!(df.height(), 1);
+        let value = |name: &str| df.column(name).unwrap().f64().unwrap().get(0);
+        assert_eq!(value("SPAIN"), Some(700.0));
+        assert_eq!(value("BELGIUM"), Some(-500.0));
+        assert_eq!(value("TOTAL"), Some(200.0));
+    }
+}
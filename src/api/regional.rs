@@ -0,0 +1,298 @@
+use core::fmt;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::actual_generation::ActualProductionType;
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct RegionalData<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for RegionalData<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Administrative region of metropolitan France. Corsica is not part of the
+/// RTE network
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum Region {
+    AuvergneRhoneAlpes,
+    BourgogneFrancheComte,
+    Bretagne,
+    CentreValDeLoire,
+    GrandEst,
+    HautsDeFrance,
+    IleDeFrance,
+    Normandie,
+    NouvelleAquitaine,
+    Occitanie,
+    PaysDeLaLoire,
+    ProvenceAlpesCoteDAzur,
+    /// A region not (yet) known by this crate
+    Unknown(String),
+}
+
+impl Region {
+    /// INSEE code of the region
+    pub fn insee_code(&self) -> Option<&str> {
+        match self {
+            Region::AuvergneRhoneAlpes => Some("84"),
+            Region::BourgogneFrancheComte => Some("27"),
+            Region::Bretagne => Some("53"),
+            Region::CentreValDeLoire => Some("24"),
+            Region::GrandEst => Some("44"),
+            Region::HautsDeFrance => Some("32"),
+            Region::IleDeFrance => Some("11"),
+            Region::Normandie => Some("28"),
+            Region::NouvelleAquitaine => Some("75"),
+            Region::Occitanie => Some("76"),
+            Region::PaysDeLaLoire => Some("52"),
+            Region::ProvenceAlpesCoteDAzur => Some("93"),
+            Region::Unknown(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let r = match self {
+            Region::AuvergneRhoneAlpes => "AUVERGNE_RHONE_ALPES",
+            Region::BourgogneFrancheComte => "BOURGOGNE_FRANCHE_COMTE",
+            Region::Bretagne => "BRETAGNE",
+            Region::CentreValDeLoire => "CENTRE_VAL_DE_LOIRE",
+            Region::GrandEst => "GRAND_EST",
+            Region::HautsDeFrance => "HAUTS_DE_FRANCE",
+            Region::IleDeFrance => "ILE_DE_FRANCE",
+            Region::Normandie => "NORMANDIE",
+            Region::NouvelleAquitaine => "NOUVELLE_AQUITAINE",
+            Region::Occitanie => "OCCITANIE",
+            Region::PaysDeLaLoire => "PAYS_DE_LA_LOIRE",
+            Region::ProvenceAlpesCoteDAzur => "PROVENCE_ALPES_COTE_D_AZUR",
+            Region::Unknown(r) => r,
+        };
+        write!(f, "{}", r)
+    }
+}
+
+impl From<String> for Region {
+    fn from(r: String) -> Self {
+        match r.as_str() {
+            "AUVERGNE_RHONE_ALPES" => Region::AuvergneRhoneAlpes,
+            "BOURGOGNE_FRANCHE_COMTE" => Region::BourgogneFrancheComte,
+            "BRETAGNE" => Region::Bretagne,
+            "CENTRE_VAL_DE_LOIRE" => Region::CentreValDeLoire,
+            "GRAND_EST" => Region::GrandEst,
+            "HAUTS_DE_FRANCE" => Region::HautsDeFrance,
+            "ILE_DE_FRANCE" => Region::IleDeFrance,
+            "NORMANDIE" => Region::Normandie,
+            "NOUVELLE_AQUITAINE" => Region::NouvelleAquitaine,
+            "OCCITANIE" => Region::Occitanie,
+            "PAYS_DE_LA_LOIRE" => Region::PaysDeLaLoire,
+            "PROVENCE_ALPES_COTE_D_AZUR" => Region::ProvenceAlpesCoteDAzur,
+            _ => Region::Unknown(r),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegionalConsumptionResponse {
+    pub regional_consumption: Vec<RegionalConsumption>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegionalConsumption {
+    pub region: Region,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<RegionalValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegionalGenerationResponse {
+    pub regional_generation: Vec<RegionalGeneration>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegionalGeneration {
+    pub region: Region,
+    pub production_type: ActualProductionType,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<RegionalValue>,
+}
+
+/// Average power over the interval, in MW
+#[derive(Deserialize, Debug)]
+pub struct RegionalValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub value: f64,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+impl<'a> RegionalData<'a> {
+    const CONSUMPTION_URL: &'static str = "/open_api/regional_data/v1/consumption";
+    const GENERATION_URL: &'static str = "/open_api/regional_data/v1/generation";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    fn query_string(regions: &[Region], date_range: Option<DateRange>) -> Vec<(String, String)> {
+        let mut qs: Vec<(String, String)> = vec![];
+
+        let mut names: Vec<String> = vec![];
+        for region in regions {
+            let name = region.to_string();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        if !names.is_empty() {
+            qs.push(("region".to_string(), names.join(",")));
+        }
+
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+        qs
+    }
+
+    /// Returns the consumption of the given regions. An empty slice returns
+    /// every region
+    pub fn consumption(
+        &self,
+        regions: &[Region],
+        date_range: Option<DateRange>,
+    ) -> Result<RegionalConsumptionResponse, anyhow::Error> {
+        let qs = RegionalData::query_string(regions, date_range);
+
+        let res: RegionalConsumptionResponse =
+            get_response(self.client, RegionalData::CONSUMPTION_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+
+    /// Returns the generation per production type of the given regions. An
+    /// empty slice returns every region
+    pub fn generation(
+        &self,
+        regions: &[Region],
+        date_range: Option<DateRange>,
+    ) -> Result<RegionalGenerationResponse, anyhow::Error> {
+        let qs = RegionalData::query_string(regions, date_range);
+
+        let res: RegionalGenerationResponse =
+            get_response(self.client, RegionalData::GENERATION_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for RegionalConsumptionResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for consumption in &self.regional_consumption {
+            if let Region::Unknown(r) = &consumption.region {
+                unknown_values.push(r.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl UnknownValues for RegionalGenerationResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for generation in &self.regional_generation {
+            if let Region::Unknown(r) = &generation.region {
+                unknown_values.push(r.clone());
+            }
+            if let ActualProductionType::Unknown(pt) = &generation.production_type {
+                unknown_values.push(pt.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl RegionalConsumptionResponse {
+    /// Returns one row per region and interval
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut regions: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut values: Vec<f64> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for consumption in &self.regional_consumption {
+            for v in &consumption.values {
+                regions.push(consumption.region.to_string());
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                values.push(v.value);
+                updated_dates.push(v.updated_date.map(|ud| ud.naive_utc()));
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("region".into(), regions),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("value".into(), values),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
+
+impl RegionalGenerationResponse {
+    /// Returns one row per region, production type and interval
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut regions: Vec<String> = vec![];
+        let mut production_types: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut values: Vec<f64> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for generation in &self.regional_generation {
+            for v in &generation.values {
+                regions.push(generation.region.to_string());
+                production_types.push(generation.production_type.to_string());
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                values.push(v.value);
+                updated_dates.push(v.updated_date.map(|ud| ud.naive_utc()));
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("region".into(), regions),
+            Series::new("production_type".into(), production_types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("value".into(), values),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
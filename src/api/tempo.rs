@@ -0,0 +1,383 @@
+use core::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Europe::Paris;
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct Tempo<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for Tempo<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+/// Color of a Tempo day
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum TempoColor {
+    Blue,
+    White,
+    Red,
+    /// A color not (yet) known by this crate
+    Unknown(String),
+}
+
+impl TempoColor {
+    /// Number of days of this color in the season starting on the 1st of
+    /// september of `season_start_year`
+    pub fn season_quota(&self, season_start_year: i32) -> Option<u32> {
+        match self {
+            TempoColor::Blue => {
+                let leap_year = NaiveDate::from_ymd_opt(season_start_year + 1, 2, 29).is_some();
+                Some(if leap_year { 301 } else { 300 })
+            }
+            TempoColor::White => Some(43),
+            TempoColor::Red => Some(22),
+            TempoColor::Unknown(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for TempoColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tc = match self {
+            TempoColor::Blue => "BLUE",
+            TempoColor::White => "WHITE",
+            TempoColor::Red => "RED",
+            TempoColor::Unknown(tc) => tc,
+        };
+        write!(f, "{}", tc)
+    }
+}
+
+impl From<String> for TempoColor {
+    fn from(tc: String) -> Self {
+        match tc.as_str() {
+            "BLUE" => TempoColor::Blue,
+            "WHITE" => TempoColor::White,
+            "RED" => TempoColor::Red,
+            _ => TempoColor::Unknown(tc),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TempoCalendarsResponse {
+    pub tempo_like_calendars: TempoCalendar,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TempoCalendar {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub values: Vec<TempoDay>,
+}
+
+/// Color of a calendar day. RTE gives the day from midnight to midnight, while
+/// the Tempo day actually runs from 06:00 to 06:00 the next day
+#[derive(Deserialize, Debug)]
+pub struct TempoDay {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "value")]
+    pub color: TempoColor,
+    pub updated_date: Option<DateTime<Utc>>,
+}
+
+impl TempoDay {
+    /// The calendar day, in french local time
+    pub fn day(&self) -> NaiveDate {
+        self.start_date.with_timezone(&Paris).date_naive()
+    }
+
+    /// Start of the Tempo day, at 06:00 french local time
+    pub fn tempo_start(&self) -> DateTime<Utc> {
+        tempo_day_start(self.day())
+    }
+
+    /// End of the Tempo day, at 06:00 french local time the next day
+    pub fn tempo_end(&self) -> DateTime<Utc> {
+        tempo_day_start(self.day() + Duration::days(1))
+    }
+}
+
+/// Returns when the Tempo day of `day` starts
+fn tempo_day_start(day: NaiveDate) -> DateTime<Utc> {
+    // 06:00 is never skipped nor repeated by daylight saving changes
+    Paris
+        .from_local_datetime(&day.and_hms_opt(6, 0, 0).unwrap())
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// Returns the Tempo day containing `at`
+pub fn tempo_day_of(at: DateTime<Utc>) -> NaiveDate {
+    let local = at.with_timezone(&Paris);
+    if local.hour() < 6 {
+        local.date_naive() - Duration::days(1)
+    } else {
+        local.date_naive()
+    }
+}
+
+/// Returns the first day of the Tempo season (1st of september) containing `day`
+pub fn season_start(day: NaiveDate) -> NaiveDate {
+    let year = if day.month() >= 9 {
+        day.year()
+    } else {
+        day.year() - 1
+    };
+    NaiveDate::from_ymd_opt(year, 9, 1).unwrap()
+}
+
+impl<'a> Tempo<'a> {
+    const URL: &'static str = "/open_api/tempo_like_supply_contract/v1/tempo_like_calendars";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns the colors of the Tempo days (06:00 to 06:00) overlapping the range
+    pub fn calendar(&self, date_range: DateRange) -> Result<TempoCalendarsResponse, anyhow::Error> {
+        let first_day = tempo_day_of(date_range.start);
+        let last_day = tempo_day_of(date_range.end - Duration::seconds(1));
+
+        self.days(first_day, last_day)
+    }
+
+    /// Returns the colors from `first_day` to `last_day`, both included
+    pub fn days(
+        &self,
+        first_day: NaiveDate,
+        last_day: NaiveDate,
+    ) -> Result<TempoCalendarsResponse, anyhow::Error> {
+        // RTE wants midnights in french local time
+        let midnight = |day: NaiveDate| {
+            Paris
+                .from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap())
+                .unwrap()
+                .format("%Y-%m-%dT%H:%M:%S%:z")
+                .to_string()
+        };
+        let qs = vec![
+            ("start_date".to_string(), midnight(first_day)),
+            (
+                "end_date".to_string(),
+                midnight(last_day + Duration::days(1)),
+            ),
+        ];
+
+        let mut res: TempoCalendarsResponse = get_response(self.client, Tempo::URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        // RTE returns the most recent days first
+        res.tempo_like_calendars
+            .values
+            .sort_by_key(|day| day.start_date);
+        Ok(res)
+    }
+
+    /// Returns the color of tomorrow, if already published (around 11:00)
+    pub fn next_day_color(&self) -> Result<Option<TempoColor>, anyhow::Error> {
+        let tomorrow = Utc::now().with_timezone(&Paris).date_naive() + Duration::days(1);
+        let res = self.days(tomorrow, tomorrow)?;
+
+        Ok(res.color_of(tomorrow))
+    }
+
+    /// Returns how many days of `color` are left in the current season,
+    /// counting the days published so far
+    pub fn remaining_days(&self, color: TempoColor) -> Result<u32, anyhow::Error> {
+        let today = Utc::now().with_timezone(&Paris).date_naive();
+        self.remaining_days_on(color, today)
+    }
+
+    /// Same as `remaining_days`, on a given day of the season
+    fn remaining_days_on(&self, color: TempoColor, today: NaiveDate) -> Result<u32, anyhow::Error> {
+        let season_start = season_start(today);
+
+        let quota = color.season_quota(season_start.year()).ok_or_else(|| {
+            anyhow::Error::msg(format!("No season quota for Tempo color {}", color))
+        })?;
+        let res = self.days(season_start, today + Duration::days(1))?;
+        let used = res.count(&color);
+
+        Ok(quota.saturating_sub(used))
+    }
+}
+
+impl UnknownValues for TempoCalendarsResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for day in &self.tempo_like_calendars.values {
+            if let TempoColor::Unknown(tc) = &day.color {
+                unknown_values.push(tc.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl TempoCalendarsResponse {
+    /// Returns the color of a given day
+    pub fn color_of(&self, day: NaiveDate) -> Option<TempoColor> {
+        self.tempo_like_calendars
+            .values
+            .iter()
+            .find(|d| d.day() == day)
+            .map(|d| d.color.clone())
+    }
+
+    /// Returns the color in force at a given time
+    pub fn color_at(&self, at: DateTime<Utc>) -> Option<TempoColor> {
+        self.color_of(tempo_day_of(at))
+    }
+
+    /// Returns the number of days of the given color
+    pub fn count(&self, color: &TempoColor) -> u32 {
+        self.tempo_like_calendars
+            .values
+            .iter()
+            .filter(|d| d.color == *color)
+            .count() as u32
+    }
+
+    /// Returns one row per Tempo day, with its actual 06:00 to 06:00 bounds
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut days: Vec<NaiveDate> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut colors: Vec<String> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for day in &self.tempo_like_calendars.values {
+            days.push(day.day());
+            start_dates.push(day.tempo_start().naive_utc());
+            end_dates.push(day.tempo_end().naive_utc());
+            colors.push(day.color.to_string());
+            updated_dates.push(day.updated_date.map(|ud| ud.naive_utc()));
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("day".into(), days),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("color".into(), colors),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn tempo_days_start_at_six() {
+        // Winter time, UTC+1
+        assert_eq!(tempo_day_of(at("2024-01-15T04:59:59Z")), date("2024-01-14"));
+        assert_eq!(tempo_day_of(at("2024-01-15T05:00:00Z")), date("2024-01-15"));
+        // Summer time, UTC+2
+        assert_eq!(tempo_day_of(at("2024-07-15T03:59:59Z")), date("2024-07-14"));
+        assert_eq!(tempo_day_of(at("2024-07-15T04:00:00Z")), date("2024-07-15"));
+        // The night of the switch to summer time belongs to the previous day
+        assert_eq!(tempo_day_of(at("2024-03-31T03:59:59Z")), date("2024-03-30"));
+        assert_eq!(tempo_day_of(at("2024-03-31T04:00:00Z")), date("2024-03-31"));
+        assert_eq!(
+            tempo_day_start(date("2024-03-30")),
+            at("2024-03-30T05:00:00Z")
+        );
+        assert_eq!(
+            tempo_day_start(date("2024-03-31")),
+            at("2024-03-31T04:00:00Z")
+        );
+    }
+
+    #[test]
+    fn seasons_start_on_september_first() {
+        assert_eq!(season_start(date("2024-08-31")), date("2023-09-01"));
+        assert_eq!(season_start(date("2024-09-01")), date("2024-09-01"));
+        assert_eq!(season_start(date("2025-01-15")), date("2024-09-01"));
+    }
+
+    #[test]
+    fn blue_quota_includes_february_29th() {
+        // The 2023-2024 season contains the 29th of february 2024
+        assert_eq!(TempoColor::Blue.season_quota(2023), Some(301));
+        assert_eq!(TempoColor::Blue.season_quota(2024), Some(300));
+        assert_eq!(TempoColor::White.season_quota(2024), Some(43));
+        assert_eq!(TempoColor::Red.season_quota(2024), Some(22));
+        assert_eq!(
+            TempoColor::Unknown("PINK".to_string()).season_quota(2024),
+            None
+        );
+    }
+
+    /// Replies with a calendar of the given colors, one day each from the
+    /// 2nd of december 2024
+    struct MockClient(Vec<&'static str>);
+
+    impl ApiClient for MockClient {
+        fn http_get(&self, _: &str, _: &[(String, String)]) -> Result<String, anyhow::Error> {
+            let days: Vec<String> = self
+                .0
+                .iter()
+                .enumerate()
+                .map(|(i, color)| {
+                    format!(
+                        r#"{{"start_date":"2024-12-{:02}T00:00:00+01:00","end_date":"2024-12-{:02}T00:00:00+01:00","value":"{color}","updated_date":null}}"#,
+                        i + 2,
+                        i + 3
+                    )
+                })
+                .collect();
+            Ok(format!(
+                r#"{{"tempo_like_calendars":{{"start_date":"2024-09-01T00:00:00+02:00","end_date":"2024-12-06T00:00:00+01:00","values":[{}]}}}}"#,
+                days.join(",")
+            ))
+        }
+    }
+
+    #[test]
+    fn remaining_days_are_the_quota_minus_the_published_days() {
+        let client = MockClient(vec!["RED", "WHITE", "RED", "BLUE"]);
+        let tempo = Tempo::new(&client);
+        let today = date("2024-12-04");
+
+        assert_eq!(tempo.remaining_days_on(TempoColor::Red, today).unwrap(), 20);
+        assert_eq!(
+            tempo.remaining_days_on(TempoColor::White, today).unwrap(),
+            42
+        );
+        assert_eq!(
+            tempo.remaining_days_on(TempoColor::Blue, today).unwrap(),
+            299
+        );
+        assert!(tempo
+            .remaining_days_on(TempoColor::Unknown("PINK".to_string()), today)
+            .is_err());
+    }
+}
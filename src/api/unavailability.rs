@@ -0,0 +1,377 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, DurationRound, NaiveDateTime, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::actual_generation::ActualProductionType;
+use super::installed_capacities::CapacitiesPerProductionUnitResponse;
+use super::{ensure_known_values, get_response, DateRange, StrictMode, UnknownValues};
+
+pub struct Unavailability<'a> {
+    client: &'a dyn ApiClient,
+    strict: bool,
+}
+
+impl StrictMode for Unavailability<'_> {
+    fn strict_mode(&mut self) -> &mut bool {
+        &mut self.strict
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum UnavailabilityType {
+    /// Indisponibilité planifiée
+    PlannedMaintenance,
+    /// Indisponibilité fortuite (forced outage)
+    Fortuitous,
+    /// An unavailability type not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for UnavailabilityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ut = match self {
+            UnavailabilityType::PlannedMaintenance => "PLANNED_MAINTENANCE",
+            UnavailabilityType::Fortuitous => "FORTUITOUS",
+            UnavailabilityType::Unknown(ut) => ut,
+        };
+        write!(f, "{}", ut)
+    }
+}
+
+impl From<String> for UnavailabilityType {
+    fn from(ut: String) -> Self {
+        match ut.as_str() {
+            "PLANNED_MAINTENANCE" => UnavailabilityType::PlannedMaintenance,
+            "FORTUITOUS" => UnavailabilityType::Fortuitous,
+            _ => UnavailabilityType::Unknown(ut),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum UnavailabilityStatus {
+    /// The unavailability is in force
+    Active,
+    /// The unavailability is over
+    Inactive,
+    /// The unavailability was cancelled
+    Dismissed,
+    /// An unavailability status not (yet) known by this crate
+    Unknown(String),
+}
+
+impl fmt::Display for UnavailabilityStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let us = match self {
+            UnavailabilityStatus::Active => "ACTIVE",
+            UnavailabilityStatus::Inactive => "INACTIVE",
+            UnavailabilityStatus::Dismissed => "DISMISSED",
+            UnavailabilityStatus::Unknown(us) => us,
+        };
+        write!(f, "{}", us)
+    }
+}
+
+impl From<String> for UnavailabilityStatus {
+    fn from(us: String) -> Self {
+        match us.as_str() {
+            "ACTIVE" => UnavailabilityStatus::Active,
+            "INACTIVE" => UnavailabilityStatus::Inactive,
+            "DISMISSED" => UnavailabilityStatus::Dismissed,
+            _ => UnavailabilityStatus::Unknown(us),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GenerationUnavailabilitiesResponse {
+    pub generation_unavailabilities: Vec<GenerationUnavailability>,
+}
+
+/// A version of an outage event of a generation unit
+#[derive(Deserialize, Debug)]
+pub struct GenerationUnavailability {
+    /// Identifier of the event, shared by all its versions
+    pub identifier: String,
+    pub version: u32,
+    pub creation_date: DateTime<Utc>,
+    pub updated_date: DateTime<Utc>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(rename = "type")]
+    pub ty: UnavailabilityType,
+    pub status: UnavailabilityStatus,
+    pub unit: UnavailableUnit,
+    pub values: Vec<UnavailabilityValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UnavailableUnit {
+    /// EIC code of the unit
+    pub eic_code: String,
+    pub name: String,
+    pub production_type: ActualProductionType,
+    /// Installed capacity, in MW
+    pub installed_capacity: f64,
+}
+
+/// Capacities of the unit during a part of the event, in MW
+#[derive(Deserialize, Debug)]
+pub struct UnavailabilityValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub available_capacity: f64,
+    pub unavailable_capacity: f64,
+}
+
+impl<'a> Unavailability<'a> {
+    const GENERATION_URL: &'static str =
+        "/open_api/unavailability_additional_information/v6/generation_unavailabilities";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self {
+            client,
+            strict: false,
+        }
+    }
+
+    /// Returns every version of the generation unit outages over the range
+    pub fn generation(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<GenerationUnavailabilitiesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        let res: GenerationUnavailabilitiesResponse =
+            get_response(self.client, Unavailability::GENERATION_URL, &qs)?;
+        if self.strict {
+            ensure_known_values(&res)?;
+        }
+        Ok(res)
+    }
+}
+
+impl UnknownValues for GenerationUnavailabilitiesResponse {
+    fn unknown_values(&self) -> Vec<String> {
+        let mut unknown_values = vec![];
+        for unavailability in &self.generation_unavailabilities {
+            if let UnavailabilityType::Unknown(ut) = &unavailability.ty {
+                unknown_values.push(ut.clone());
+            }
+            if let UnavailabilityStatus::Unknown(us) = &unavailability.status {
+                unknown_values.push(us.clone());
+            }
+            if let ActualProductionType::Unknown(pt) = &unavailability.unit.production_type {
+                unknown_values.push(pt.clone());
+            }
+        }
+        unknown_values
+    }
+}
+
+impl GenerationUnavailabilitiesResponse {
+    /// Returns one row per capacity period of each event version
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut identifiers: Vec<&str> = vec![];
+        let mut versions: Vec<u32> = vec![];
+        let mut types: Vec<String> = vec![];
+        let mut statuses: Vec<String> = vec![];
+        let mut eic_codes: Vec<&str> = vec![];
+        let mut names: Vec<&str> = vec![];
+        let mut production_types: Vec<String> = vec![];
+        let mut installed_capacities: Vec<f64> = vec![];
+        let mut updated_dates: Vec<NaiveDateTime> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut available_capacities: Vec<f64> = vec![];
+        let mut unavailable_capacities: Vec<f64> = vec![];
+
+        for unavailability in &self.generation_unavailabilities {
+            for v in &unavailability.values {
+                identifiers.push(&unavailability.identifier);
+                versions.push(unavailability.version);
+                types.push(unavailability.ty.to_string());
+                statuses.push(unavailability.status.to_string());
+                eic_codes.push(&unavailability.unit.eic_code);
+                names.push(&unavailability.unit.name);
+                production_types.push(unavailability.unit.production_type.to_string());
+                installed_capacities.push(unavailability.unit.installed_capacity);
+                updated_dates.push(unavailability.updated_date.naive_utc());
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                available_capacities.push(v.available_capacity);
+                unavailable_capacities.push(v.unavailable_capacity);
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("identifier".into(), identifiers),
+            Series::new("version".into(), versions),
+            Series::new("type".into(), types),
+            Series::new("status".into(), statuses),
+            Series::new("eic_code".into(), eic_codes),
+            Series::new("name".into(), names),
+            Series::new("production_type".into(), production_types),
+            Series::new("installed_capacity".into(), installed_capacities),
+            Series::new("updated_date".into(), updated_dates),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("available_capacity".into(), available_capacities),
+            Series::new("unavailable_capacity".into(), unavailable_capacities),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Returns the latest version of each event, dropping the dismissed ones
+    pub fn latest_versions(&self) -> Vec<&GenerationUnavailability> {
+        let mut latest: BTreeMap<&str, &GenerationUnavailability> = BTreeMap::new();
+        for unavailability in &self.generation_unavailabilities {
+            match latest.get(unavailability.identifier.as_str()) {
+                Some(current) if current.version >= unavailability.version => {}
+                _ => {
+                    latest.insert(&unavailability.identifier, unavailability);
+                }
+            }
+        }
+
+        latest
+            .into_values()
+            .filter(|u| u.status != UnavailabilityStatus::Dismissed)
+            .collect()
+    }
+
+    /// Returns the hourly available capacity of each unit of `fleet` in service
+    /// over the range, from the latest version of each event. Units without any outage
+    /// are fully available, and units missing from `fleet` are added with the
+    /// installed capacity reported by their events. When several events
+    /// overlap an hour, the lowest available capacity is kept
+    pub fn availability_timeline(
+        &self,
+        fleet: &CapacitiesPerProductionUnitResponse,
+        date_range: &DateRange,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let events = self.latest_versions();
+
+        // Units keyed by EIC code, with the installed capacity of their latest
+        // record over the range
+        let mut units: BTreeMap<&str, (&str, &ActualProductionType, f64)> = BTreeMap::new();
+        let mut latest_records: BTreeMap<&str, DateTime<Utc>> = BTreeMap::new();
+        for capacity in fleet
+            .capacities_per_production_unit
+            .iter()
+            .flat_map(|c| &c.values)
+            // Units decommissioned before or commissioned after the range
+            .filter(|c| c.start_date < date_range.end && c.end_date > date_range.start)
+        {
+            let code = capacity.code_eic.as_str();
+            if latest_records
+                .get(code)
+                .is_none_or(|end| *end <= capacity.end_date)
+            {
+                latest_records.insert(code, capacity.end_date);
+                units.insert(
+                    code,
+                    (
+                        &capacity.name,
+                        &capacity.production_type,
+                        capacity.installed_capacity,
+                    ),
+                );
+            }
+        }
+
+        // Capacity periods of the events, grouped by unit
+        let mut values: BTreeMap<&str, Vec<&UnavailabilityValue>> = BTreeMap::new();
+        for event in &events {
+            let unit = &event.unit;
+            units.entry(&unit.eic_code).or_insert((
+                &unit.name,
+                &unit.production_type,
+                unit.installed_capacity,
+            ));
+            values
+                .entry(&unit.eic_code)
+                .or_default()
+                .extend(&event.values);
+        }
+
+        let mut eic_codes: Vec<&str> = vec![];
+        let mut names: Vec<&str> = vec![];
+        let mut production_types: Vec<String> = vec![];
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut installed_capacities: Vec<f64> = vec![];
+        let mut available_capacities: Vec<f64> = vec![];
+
+        let first_hour = date_range.start.duration_trunc(Duration::hours(1))?;
+        for (eic_code, (name, production_type, installed_capacity)) in units {
+            let unit_values = values.get(eic_code).map(Vec::as_slice).unwrap_or_default();
+
+            let mut hour = first_hour;
+            while hour < date_range.end {
+                let next_hour = hour + Duration::hours(1);
+
+                let available = unit_values
+                    .iter()
+                    .filter(|v| v.start_date < next_hour && v.end_date > hour)
+                    .map(|v| v.available_capacity)
+                    .fold(installed_capacity, f64::min);
+
+                eic_codes.push(eic_code);
+                names.push(name);
+                production_types.push(production_type.to_string());
+                start_dates.push(hour.naive_utc());
+                end_dates.push(next_hour.naive_utc());
+                installed_capacities.push(installed_capacity);
+                available_capacities.push(available);
+
+                hour = next_hour;
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("eic_code".into(), eic_codes),
+            Series::new("name".into(), names),
+            Series::new("production_type".into(), production_types),
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("installed_capacity".into(), installed_capacities),
+            Series::new("available_capacity".into(), available_capacities),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Same as `availability_timeline`, summed per production type
+    pub fn availability_timeline_per_production_type(
+        &self,
+        fleet: &CapacitiesPerProductionUnitResponse,
+        date_range: &DateRange,
+    ) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let by = [col("production_type"), col("start_date"), col("end_date")];
+
+        let df = self
+            .availability_timeline(fleet, date_range)?
+            .lazy()
+            .group_by(by.clone())
+            .agg([
+                col("installed_capacity").sum(),
+                col("available_capacity").sum(),
+            ])
+            .sort_by_exprs(by, SortMultipleOptions::default())
+            .collect()?;
+
+        Ok(df)
+    }
+}
@@ -0,0 +1,167 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Timelike, Utc};
+use polars::prelude::*;
+use serde::Deserialize;
+
+use crate::ApiClient;
+
+use super::{get_response, DateRange};
+
+pub struct WholesaleMarket<'a> {
+    client: &'a dyn ApiClient,
+}
+
+/// Length of the market time unit of a price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Hourly,
+    QuarterHourly,
+}
+
+impl Resolution {
+    fn of(start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Option<Resolution> {
+        match (end_date - start_date).num_minutes() {
+            60 => Some(Resolution::Hourly),
+            15 => Some(Resolution::QuarterHourly),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let r = match self {
+            Resolution::Hourly => "PT60M",
+            Resolution::QuarterHourly => "PT15M",
+        };
+        write!(f, "{}", r)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FrancePowerExchangesResponse {
+    pub france_power_exchanges: Vec<FrancePowerExchanges>,
+}
+
+/// Day-ahead auction results of a delivery day
+#[derive(Deserialize, Debug)]
+pub struct FrancePowerExchanges {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub updated_date: Option<DateTime<Utc>>,
+    pub values: Vec<DayAheadValue>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DayAheadValue {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Traded volume, in MWh
+    #[serde(rename = "value")]
+    pub volume: f64,
+    /// Price, in EUR/MWh
+    pub price: f64,
+}
+
+impl DayAheadValue {
+    /// `None` for time units other than 15 minutes or one hour
+    pub fn resolution(&self) -> Option<Resolution> {
+        Resolution::of(self.start_date, self.end_date)
+    }
+}
+
+impl<'a> WholesaleMarket<'a> {
+    const URL: &'static str = "/open_api/wholesale_market/v2/france_power_exchanges";
+
+    pub fn new(client: &'a dyn ApiClient) -> Self {
+        Self { client }
+    }
+
+    /// Returns the french day-ahead prices and volumes
+    pub fn day_ahead(
+        &self,
+        date_range: Option<DateRange>,
+    ) -> Result<FrancePowerExchangesResponse, anyhow::Error> {
+        let mut qs: Vec<(String, String)> = vec![];
+        if let Some(date_range) = date_range {
+            qs.append(&mut date_range.to_query_string());
+        }
+
+        get_response(self.client, WholesaleMarket::URL, &qs)
+    }
+}
+
+impl FrancePowerExchangesResponse {
+    /// Returns one row per market time unit, 15 minutes or one hour long
+    pub fn as_polars_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        let mut start_dates: Vec<NaiveDateTime> = vec![];
+        let mut end_dates: Vec<NaiveDateTime> = vec![];
+        let mut prices: Vec<f64> = vec![];
+        let mut volumes: Vec<f64> = vec![];
+        let mut resolutions: Vec<Option<String>> = vec![];
+        let mut updated_dates: Vec<Option<NaiveDateTime>> = vec![];
+
+        for day in &self.france_power_exchanges {
+            for v in &day.values {
+                start_dates.push(v.start_date.naive_utc());
+                end_dates.push(v.end_date.naive_utc());
+                prices.push(v.price);
+                volumes.push(v.volume);
+                resolutions.push(v.resolution().map(|r| r.to_string()));
+                updated_dates.push(day.updated_date.map(|ud| ud.naive_utc()));
+            }
+        }
+
+        let df = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("price".into(), prices),
+            Series::new("volume".into(), volumes),
+            Series::new("resolution".into(), resolutions),
+            Series::new("updated_date".into(), updated_dates),
+        ])?;
+
+        Ok(df)
+    }
+
+    /// Returns one row per hour, summing the volumes of quarter-hourly time
+    /// units and averaging their prices weighted by volume, to join with
+    /// hourly forecasts. The price is null when no volume was traded
+    pub fn hourly_df(&self) -> Result<polars::prelude::DataFrame, anyhow::Error> {
+        // price * volume, volume
+        let mut hours: BTreeMap<NaiveDateTime, (f64, f64)> = BTreeMap::new();
+
+        for day in &self.france_power_exchanges {
+            for v in &day.values {
+                let hour = v
+                    .start_date
+                    .naive_utc()
+                    .with_minute(0)
+                    .and_then(|h| h.with_second(0))
+                    .unwrap();
+                let entry = hours.entry(hour).or_default();
+                entry.0 += v.price * v.volume;
+                entry.1 += v.volume;
+            }
+        }
+
+        let start_dates: Vec<NaiveDateTime> = hours.keys().copied().collect();
+        let end_dates: Vec<NaiveDateTime> = hours.keys().map(|h| *h + Duration::hours(1)).collect();
+        let prices: Vec<Option<f64>> = hours
+            .values()
+            .map(|(weighted_price, volume)| (*volume > 0.0).then(|| weighted_price / volume))
+            .collect();
+        let volumes: Vec<f64> = hours.values().map(|(_, v)| *v).collect();
+
+        let df = DataFrame::new(vec![
+            Series::new("start_date".into(), start_dates),
+            Series::new("end_date".into(), end_dates),
+            Series::new("price".into(), prices),
+            Series::new("volume".into(), volumes),
+        ])?;
+
+        Ok(df)
+    }
+}
@@ -0,0 +1,203 @@
+use polars::prelude::*;
+
+use crate::api::consumption::{ConsumptionForecast, ShortTermForecastType};
+use crate::api::DateRange;
+
+/// How the forecast errors are grouped, on top of the forecast horizon.
+/// Hours, weekdays and months are in french local time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    /// Only by forecast horizon
+    Horizon,
+    /// By hour of the day (0 to 23)
+    HourOfDay,
+    /// By day of the week (monday = 1 to sunday = 7)
+    Weekday,
+    /// By month (1 to 12)
+    Month,
+}
+
+impl Grouping {
+    fn column(&self) -> Option<(&'static str, Expr)> {
+        let local_start = col("start_date")
+            .dt()
+            .replace_time_zone(Some("UTC".into()), lit("raise"), NonExistent::Raise)
+            .dt()
+            .convert_time_zone("Europe/Paris".into());
+
+        match self {
+            Grouping::Horizon => None,
+            Grouping::HourOfDay => Some(("hour", local_start.dt().hour())),
+            Grouping::Weekday => Some(("weekday", local_start.dt().weekday())),
+            Grouping::Month => Some(("month", local_start.dt().month())),
+        }
+    }
+}
+
+/// The forecast types that are evaluated
+const HORIZONS: [ShortTermForecastType; 3] = [
+    ShortTermForecastType::Intraday,
+    ShortTermForecastType::Tomorrow,
+    ShortTermForecastType::DayAfterTomorrow,
+];
+
+/// Consumption forecasts (ID, D-1 and D-2) paired with the realised
+/// consumption of the same intervals, to measure their accuracy
+#[derive(Debug)]
+pub struct ForecastEvaluation {
+    errors: DataFrame,
+}
+
+impl ForecastEvaluation {
+    /// Pairs the forecasts of a long format consumption frame with its
+    /// realised values, e.g. from `ShortTermResponse::as_polars_df` or
+    /// `VintageStore::consumption_history`.
+    /// When several vintages of a forecast exist, the latest one is used.
+    /// Types other than ID, D-1, D-2 and REALISED are ignored
+    pub fn from_polars_df(df: DataFrame) -> Result<Self, anyhow::Error> {
+        let realised = ShortTermForecastType::Realised.to_string();
+        let is_horizon = HORIZONS
+            .iter()
+            .map(|ft| col("type").eq(lit(ft.to_string())))
+            .reduce(|acc, e| acc.or(e))
+            .unwrap();
+
+        let latest = df
+            .lazy()
+            .sort(
+                ["updated_date"],
+                SortMultipleOptions::default().with_maintain_order(true),
+            )
+            .unique_stable(
+                Some(vec!["type".into(), "start_date".into(), "end_date".into()]),
+                UniqueKeepStrategy::Last,
+            );
+
+        let realised_values = latest
+            .clone()
+            .filter(col("type").eq(lit(realised.as_str())))
+            .select([
+                col("start_date"),
+                col("end_date"),
+                col("value").alias("realised"),
+            ]);
+
+        let errors = latest
+            .filter(is_horizon)
+            .join(
+                realised_values,
+                [col("start_date"), col("end_date")],
+                [col("start_date"), col("end_date")],
+                JoinArgs::new(JoinType::Inner),
+            )
+            .select([
+                col("type"),
+                col("start_date"),
+                col("end_date"),
+                col("value").alias("forecast"),
+                col("realised"),
+                (col("value") - col("realised")).alias("error"),
+            ])
+            .sort(["type", "start_date"], SortMultipleOptions::default())
+            .collect()?;
+
+        Ok(ForecastEvaluation { errors })
+    }
+
+    /// Fetches the ID, D-1 and D-2 forecasts along with the realised
+    /// consumption over the given range
+    pub fn fetch(
+        consumption_forecast: &ConsumptionForecast,
+        date_range: DateRange,
+    ) -> Result<Self, anyhow::Error> {
+        let mut forecast_types = HORIZONS.to_vec();
+        forecast_types.push(ShortTermForecastType::Realised);
+
+        let df = consumption_forecast
+            .short_terms(&forecast_types, Some(date_range))?
+            .as_polars_df()?;
+
+        ForecastEvaluation::from_polars_df(df)
+    }
+
+    /// Returns the paired forecast and realised values, one row per forecast
+    /// type and interval. `error` is the forecast minus the realised value
+    pub fn errors(&self) -> &DataFrame {
+        &self.errors
+    }
+
+    /// Returns the accuracy metrics per forecast type and grouping: MAE, RMSE,
+    /// MAPE (in %), bias, and the 50th, 90th and 95th percentiles of the
+    /// absolute error
+    pub fn metrics(&self, grouping: Grouping) -> Result<DataFrame, anyhow::Error> {
+        let mut by = vec![col("type")];
+        let mut lf = self.errors.clone().lazy();
+        if let Some((name, expr)) = grouping.column() {
+            lf = lf.with_column(expr.alias(name));
+            by.push(col(name));
+        }
+
+        let abs_error = col("error").abs();
+        let percentile = |q: f64| {
+            abs_error
+                .clone()
+                .quantile(lit(q), QuantileInterpolOptions::Linear)
+        };
+
+        let df = lf
+            .group_by(by.clone())
+            .agg([
+                len().alias("count"),
+                abs_error.clone().mean().alias("mae"),
+                col("error").pow(2).mean().sqrt().alias("rmse"),
+                ((abs_error.clone() / col("realised").abs()).mean() * lit(100.0)).alias("mape"),
+                col("error").mean().alias("bias"),
+                percentile(0.5).alias("p50"),
+                percentile(0.9).alias("p90"),
+                percentile(0.95).alias("p95"),
+            ])
+            .sort_by_exprs(by, SortMultipleOptions::default())
+            .collect()?;
+
+        Ok(df)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consumption::ShortTermResponse;
+
+    #[test]
+    fn only_known_horizons_are_evaluated() {
+        let series = |ty: &str, value: f64| {
+            format!(
+                r#"{{"type":"{ty}","start_date":"2024-10-24T00:00:00+02:00","end_date":"2024-10-24T00:30:00+02:00","values":[
+                {{"start_date":"2024-10-24T00:00:00+02:00","end_date":"2024-10-24T00:30:00+02:00","updated_date":"2024-10-24T01:00:00+02:00","value":{value}}}]}}"#
+            )
+        };
+        let reply = format!(
+            r#"{{"short_term":[{},{},{}]}}"#,
+            series("REALISED", 50000.0),
+            series("D-1", 51000.0),
+            series("FOO", 40000.0)
+        );
+        let response: ShortTermResponse = serde_json::from_str(&reply).unwrap();
+
+        let evaluation =
+            ForecastEvaluation::from_polars_df(response.as_polars_df().unwrap()).unwrap();
+        let errors = evaluation.errors();
+        assert_eq!(errors.height(), 1);
+        assert_eq!(
+            errors.column("type").unwrap().str().unwrap().get(0),
+            Some("D-1")
+        );
+        assert_eq!(
+            errors.column("error").unwrap().f64().unwrap().get(0),
+            Some(1000.0)
+        );
+
+        let metrics = evaluation.metrics(Grouping::Horizon).unwrap();
+        assert_eq!(metrics.height(), 1);
+    }
+}
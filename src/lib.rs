@@ -3,6 +3,8 @@ use oauth2::AccessToken;
 use oauth2::{basic::BasicClient, AuthUrl, ClientId, ClientSecret, TokenResponse, TokenUrl};
 
 pub mod api;
+pub mod evaluation;
+pub mod vintage_store;
 //use api::generation::GenerationForecast;
 
 const PRODUCTION_BASE_URL: &str = "https://digital.iservices.rte-france.com/";
@@ -0,0 +1,327 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+
+use crate::api::consumption::{ConsumptionForecast, ShortTermForecastType};
+use crate::api::generation::{ForecastType, GenerationForecast, ProductionType};
+use crate::api::DateRange;
+
+/// Local append-only store of the successive forecast vintages (identified by
+/// their `updated_date`) published by RTE.
+///
+/// Every poll writes the records that were not seen before in a new parquet
+/// file, so that the forecast as known at any point in time can be rebuilt.
+#[derive(Debug)]
+pub struct VintageStore {
+    directory: PathBuf,
+}
+
+/// Columns identifying a generation forecast target
+const GENERATION_KEY: [&str; 5] = [
+    "production_type",
+    "type",
+    "sub_type",
+    "start_date",
+    "end_date",
+];
+
+/// Columns identifying a consumption forecast target
+const CONSUMPTION_KEY: [&str; 3] = ["type", "start_date", "end_date"];
+
+impl VintageStore {
+    const GENERATION_DIR: &'static str = "generation";
+    const CONSUMPTION_DIR: &'static str = "consumption";
+
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        VintageStore {
+            directory: directory.into(),
+        }
+    }
+
+    /// Fetches the current generation forecasts and stores the new vintages.
+    /// Returns the number of newly stored records
+    pub fn poll_generation(
+        &self,
+        generation_forecast: &GenerationForecast,
+        production_type: Option<ProductionType>,
+        forecast_type: Option<ForecastType>,
+        date_range: Option<DateRange>,
+    ) -> Result<usize, anyhow::Error> {
+        let df = generation_forecast
+            .short_term(production_type, forecast_type, date_range)?
+            .as_polars_df()?;
+
+        self.append(VintageStore::GENERATION_DIR, df, &GENERATION_KEY)
+    }
+
+    /// Fetches the current consumption forecasts and stores the new vintages.
+    /// Returns the number of newly stored records
+    pub fn poll_consumption(
+        &self,
+        consumption_forecast: &ConsumptionForecast,
+        forecast_types: &[ShortTermForecastType],
+        date_range: Option<DateRange>,
+    ) -> Result<usize, anyhow::Error> {
+        let df = consumption_forecast
+            .short_terms(forecast_types, date_range)?
+            .as_polars_df()?;
+
+        self.append(VintageStore::CONSUMPTION_DIR, df, &CONSUMPTION_KEY)
+    }
+
+    /// Returns every stored generation forecast vintage
+    pub fn generation_history(&self) -> Result<DataFrame, anyhow::Error> {
+        Ok(self.read(VintageStore::GENERATION_DIR)?.collect()?)
+    }
+
+    /// Returns every stored consumption forecast vintage
+    pub fn consumption_history(&self) -> Result<DataFrame, anyhow::Error> {
+        Ok(self.read(VintageStore::CONSUMPTION_DIR)?.collect()?)
+    }
+
+    /// Returns the generation forecasts as they were known at `as_of`
+    pub fn generation_as_of(&self, as_of: DateTime<Utc>) -> Result<DataFrame, anyhow::Error> {
+        VintageStore::as_of(
+            self.read(VintageStore::GENERATION_DIR)?,
+            as_of,
+            &GENERATION_KEY,
+        )
+    }
+
+    /// Returns the consumption forecasts as they were known at `as_of`
+    pub fn consumption_as_of(&self, as_of: DateTime<Utc>) -> Result<DataFrame, anyhow::Error> {
+        VintageStore::as_of(
+            self.read(VintageStore::CONSUMPTION_DIR)?,
+            as_of,
+            &CONSUMPTION_KEY,
+        )
+    }
+
+    /// Keeps, for each target, the latest vintage published before `as_of`
+    fn as_of(
+        history: LazyFrame,
+        as_of: DateTime<Utc>,
+        key: &[&str],
+    ) -> Result<DataFrame, anyhow::Error> {
+        let df = history
+            .filter(col("updated_date").lt_eq(lit(as_of.naive_utc())))
+            .sort(
+                ["updated_date"],
+                SortMultipleOptions::default().with_maintain_order(true),
+            )
+            .unique_stable(
+                Some(key.iter().map(|k| (*k).into()).collect()),
+                UniqueKeepStrategy::Last,
+            )
+            .sort(key.to_vec(), SortMultipleOptions::default())
+            .collect()?;
+
+        Ok(df)
+    }
+
+    /// Writes the records of `df` that are not stored yet in a new file
+    fn append(&self, kind: &str, mut df: DataFrame, key: &[&str]) -> Result<usize, anyhow::Error> {
+        let dir = self.directory.join(kind);
+        fs::create_dir_all(&dir)?;
+
+        if VintageStore::files(&dir)?.next().is_some() {
+            // A vintage is a target's value at a given update
+            let vintage: Vec<Expr> = key
+                .iter()
+                .chain(&["updated_date", "value"])
+                .map(|c| col(*c))
+                .collect();
+            let mut args = JoinArgs::new(JoinType::Anti);
+            args.join_nulls = true;
+
+            df = df
+                .lazy()
+                .join(self.read(kind)?, &vintage, &vintage, args)
+                .collect()?;
+        }
+        df = df.unique_stable(None, UniqueKeepStrategy::First, None)?;
+
+        let new_records = df.height();
+        if new_records > 0 {
+            // Two polls within the same millisecond must not overwrite each other
+            let mut timestamp = Utc::now().timestamp_millis();
+            let mut path = dir.join(format!("{}.parquet", timestamp));
+            while path.exists() {
+                timestamp += 1;
+                path = dir.join(format!("{}.parquet", timestamp));
+            }
+            ParquetWriter::new(File::create(path)?).finish(&mut df)?;
+        }
+
+        Ok(new_records)
+    }
+
+    /// Scans the stored files lazily, so that a query only loads the columns
+    /// and rows it needs instead of the whole history
+    fn read(&self, kind: &str) -> Result<LazyFrame, anyhow::Error> {
+        let dir = self.directory.join(kind);
+
+        if VintageStore::files(&dir)?.next().is_none() {
+            return Err(anyhow::Error::msg(format!(
+                "No {} vintage stored in {}",
+                kind,
+                self.directory.display()
+            )));
+        }
+
+        Ok(LazyFrame::scan_parquet(
+            dir.join("*.parquet"),
+            ScanArgsParquet::default(),
+        )?)
+    }
+
+    fn files(dir: &Path) -> Result<impl Iterator<Item = PathBuf>, anyhow::Error> {
+        let mut files = vec![];
+        if !dir.exists() {
+            return Ok(files.into_iter());
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "parquet") {
+                files.push(path);
+            }
+        }
+        files.sort();
+
+        Ok(files.into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::ApiClient;
+
+    /// Replies with the given body, or fails like a rate limited request
+    struct MockClient {
+        reply: RefCell<Option<String>>,
+    }
+
+    impl MockClient {
+        fn new() -> Self {
+            MockClient {
+                reply: RefCell::new(None),
+            }
+        }
+
+        /// Publishes a D-1 forecast of two intervals, updated at `updated_date`
+        fn publish(&self, updated_date: &str, values: [f64; 2]) {
+            let reply = format!(
+                r#"{{"short_term":[{{"type":"D-1","start_date":"2024-10-24T00:00:00+02:00","end_date":"2024-10-24T01:00:00+02:00","values":[
+                {{"start_date":"2024-10-24T00:00:00+02:00","end_date":"2024-10-24T00:30:00+02:00","updated_date":"{updated_date}","value":{}}},
+                {{"start_date":"2024-10-24T00:30:00+02:00","end_date":"2024-10-24T01:00:00+02:00","updated_date":"{updated_date}","value":{}}}]}}]}}"#,
+                values[0], values[1]
+            );
+            self.reply.replace(Some(reply));
+        }
+    }
+
+    impl ApiClient for MockClient {
+        fn http_get(&self, _: &str, _: &[(String, String)]) -> Result<String, anyhow::Error> {
+            self.reply
+                .borrow()
+                .clone()
+                .ok_or_else(|| anyhow::Error::msg("HTTP 429"))
+        }
+    }
+
+    /// A fresh store in the temporary directory, removed when dropped
+    struct TempStore(VintageStore);
+
+    impl TempStore {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "rte-france-vintage-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            TempStore(VintageStore::new(dir))
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0.directory);
+        }
+    }
+
+    fn values(df: &DataFrame) -> Vec<Option<f64>> {
+        df.column("value")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn polls_only_store_new_vintages() {
+        let store = TempStore::new("dedup");
+        let client = MockClient::new();
+        let cf = ConsumptionForecast::new(&client);
+
+        client.publish("2024-10-23T10:00:00+02:00", [50000.0, 51000.0]);
+        assert_eq!(store.0.poll_consumption(&cf, &[], None).unwrap(), 2);
+        assert_eq!(store.0.poll_consumption(&cf, &[], None).unwrap(), 0);
+
+        // Both intervals are republished, even if only the second one changed
+        client.publish("2024-10-23T16:00:00+02:00", [50000.0, 52000.0]);
+        assert_eq!(store.0.poll_consumption(&cf, &[], None).unwrap(), 2);
+        assert_eq!(store.0.poll_consumption(&cf, &[], None).unwrap(), 0);
+
+        assert_eq!(store.0.consumption_history().unwrap().height(), 4);
+    }
+
+    #[test]
+    fn as_of_returns_the_latest_known_vintage() {
+        let store = TempStore::new("as-of");
+        let client = MockClient::new();
+        let cf = ConsumptionForecast::new(&client);
+
+        client.publish("2024-10-23T10:00:00+02:00", [50000.0, 51000.0]);
+        store.0.poll_consumption(&cf, &[], None).unwrap();
+        client.publish("2024-10-23T16:00:00+02:00", [50500.0, 52000.0]);
+        store.0.poll_consumption(&cf, &[], None).unwrap();
+
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+        let df = store
+            .0
+            .consumption_as_of(at("2024-10-23T12:00:00Z"))
+            .unwrap();
+        assert_eq!(values(&df), vec![Some(50000.0), Some(51000.0)]);
+
+        let df = store
+            .0
+            .consumption_as_of(at("2024-10-23T18:00:00Z"))
+            .unwrap();
+        assert_eq!(values(&df), vec![Some(50500.0), Some(52000.0)]);
+
+        let df = store
+            .0
+            .consumption_as_of(at("2024-10-23T00:00:00Z"))
+            .unwrap();
+        assert_eq!(df.height(), 0);
+    }
+
+    #[test]
+    fn http_errors_are_returned() {
+        let store = TempStore::new("http-error");
+        let client = MockClient::new();
+        let cf = ConsumptionForecast::new(&client);
+        let gf = GenerationForecast::new(&client);
+
+        assert!(store.0.poll_consumption(&cf, &[], None).is_err());
+        assert!(store.0.poll_generation(&gf, None, None, None).is_err());
+    }
+}